@@ -28,19 +28,12 @@ pub fn run<F: FnMut(&Ui) -> bool>(title: String, clear_color: [f32; 4], mut run_
     let mut encoder: gfx::Encoder<_, _> = factory.create_command_buffer().into();
     let shaders = {
         let version = device.get_info().shading_language;
-        if version.is_embedded {
-            if version.major >= 3 {
-                Shaders::GlSlEs300
-            } else {
-                Shaders::GlSlEs100
-            }
-        } else if version.major >= 4 {
-            Shaders::GlSl400
-        } else if version.major >= 3 {
-            Shaders::GlSl130
-        } else {
-            Shaders::GlSl110
-        }
+        Shaders::detect(&format!(
+            "{}{}.{}",
+            if version.is_embedded { "OpenGL ES " } else { "" },
+            version.major,
+            version.minor
+        ))
     };
 
     let mut imgui = ImGui::init();
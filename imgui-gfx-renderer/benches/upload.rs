@@ -0,0 +1,86 @@
+//! Measures how long packing the draw lists of a frame into the frame-wide buffers takes, with
+//! the indices kept as-is and rebased for devices without base vertex support, and how much
+//! data is then uploaded per frame.
+//!
+//! Run with `cargo bench -p imgui-gfx-renderer`.
+
+extern crate imgui;
+
+use imgui::{DrawList, ImDrawIdx, ImDrawVert, ImVec4};
+use imgui::sys::ImDrawCmd;
+use std::ptr;
+use std::time::Instant;
+
+#[path = "../src/upload.rs"]
+#[allow(dead_code)]
+mod upload;
+
+use upload::FrameBuffers;
+
+struct SyntheticList {
+    vtx: Vec<ImDrawVert>,
+    idx: Vec<ImDrawIdx>,
+    cmds: Vec<ImDrawCmd>,
+}
+
+impl SyntheticList {
+    fn new(vtx_count: usize, cmd_count: usize) -> SyntheticList {
+        let idx_count = vtx_count / 4 * 6;
+        let elem_count = (idx_count / cmd_count) as u32;
+        SyntheticList {
+            vtx: vec![ImDrawVert::default(); vtx_count],
+            idx: (0..idx_count).map(|i| (i % vtx_count) as ImDrawIdx).collect(),
+            cmds: (0..cmd_count)
+                .map(|_| ImDrawCmd {
+                    elem_count: elem_count,
+                    clip_rect: ImVec4::new(0.0, 0.0, 1024.0, 768.0),
+                    texture_id: ptr::null_mut(),
+                    user_callback: None,
+                    user_callback_data: ptr::null_mut(),
+                })
+                .collect(),
+        }
+    }
+    fn draw_list<'a>(&'a self) -> DrawList<'a> {
+        DrawList {
+            cmd_buffer: &self.cmds,
+            idx_buffer: &self.idx,
+            vtx_buffer: &self.vtx,
+        }
+    }
+}
+
+fn pack(lists: &[SyntheticList], rebase_indices: bool) {
+    let mut frame = FrameBuffers::default();
+    frame.set_rebase_indices(rebase_indices);
+    let iterations = 1_000;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        frame.clear();
+        for list in lists {
+            frame.push_draw_list(&list.draw_list(), |_| None);
+        }
+    }
+    let elapsed = start.elapsed();
+    let nanos = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+
+    println!("rebased indices:          {}", rebase_indices);
+    println!("  draw commands:          {}", frame.cmds.len());
+    println!("  uploaded bytes:         {}", frame.vtx_bytes() + frame.idx_bytes());
+    println!("  buffer updates:         {}", frame.batches.len() + 1);
+    println!("  packing time per frame: {} ns", nanos / iterations);
+}
+
+fn main() {
+    // Roughly the shape of the test window with a few extra windows open
+    let lists = vec![
+        SyntheticList::new(12_000, 60),
+        SyntheticList::new(4_000, 20),
+        SyntheticList::new(1_200, 6),
+        SyntheticList::new(400, 2),
+    ];
+
+    println!("draw lists:               {}", lists.len());
+    pack(&lists, false);
+    pack(&lists, true);
+}
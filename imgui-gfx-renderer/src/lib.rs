@@ -3,9 +3,19 @@ extern crate gfx;
 extern crate imgui;
 
 use gfx::{Bind, Bundle, CommandBuffer, Encoder, Factory, IntoIndexBuffer, Rect, Resources, Slice};
-use gfx::handle::{Buffer, RenderTargetView};
+use gfx::format::{ChannelType, Format, Formatted, RenderFormat, SurfaceType};
+use gfx::handle::{Buffer, RenderTargetView, Sampler, ShaderResourceView};
+use gfx::memory::Typed;
 use gfx::traits::FactoryExt;
-use imgui::{DrawList, ImDrawIdx, ImDrawVert, ImGui, Ui};
+use imgui::{DrawCallbackContext, ImDrawIdx, ImDrawVert, ImGui, ImVec2, ImVec4, TextureId,
+            Textures, Ui};
+use imgui::render_util::grow_capacity;
+use std::any::Any;
+use std::fmt;
+
+use upload::FrameBuffers;
+
+mod upload;
 
 pub type RendererResult<T> = Result<T, RendererError>;
 
@@ -15,6 +25,31 @@ pub enum RendererError {
     Buffer(gfx::buffer::CreationError),
     Pipeline(gfx::PipelineStateError<String>),
     Combined(gfx::CombinedError),
+    /// None of the shaders in the fallback chain could be used. Contains every shader version
+    /// that was tried, in order, along with the error it failed with.
+    NoCompatibleShaders(Vec<(Shaders, gfx::PipelineStateError<String>)>),
+    /// A draw command uses a texture id which isn't in the renderer's `Textures`.
+    BadTexture(TextureId),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::RendererError::*;
+        match *self {
+            Update(ref e) => write!(f, "Buffer update failed: {:?}", e),
+            Buffer(ref e) => write!(f, "Buffer creation failed: {:?}", e),
+            Pipeline(ref e) => write!(f, "Pipeline creation failed: {:?}", e),
+            Combined(ref e) => write!(f, "Texture creation failed: {:?}", e),
+            NoCompatibleShaders(ref tried) => {
+                write!(f, "No compatible shaders found, tried:")?;
+                for &(shaders, ref e) in tried {
+                    write!(f, "\n  {:?}: {:?}", shaders, e)?;
+                }
+                Ok(())
+            }
+            BadTexture(id) => write!(f, "No texture with id {}", id.id()),
+        }
+    }
 }
 
 impl From<gfx::UpdateError<usize>> for RendererError {
@@ -38,11 +73,38 @@ gfx_defines!{
         vertex_buffer: gfx::VertexBuffer<ImDrawVert> = (),
         matrix: gfx::Global<[[f32; 4]; 4]> = "matrix",
         tex: gfx::TextureSampler<[f32; 4]> = "tex",
-        out: gfx::BlendTarget<gfx::format::Rgba8> = ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
+        out: gfx::RawRenderTarget = (
+            "Target0",
+            Format(SurfaceType::R8_G8_B8_A8, ChannelType::Unorm),
+            gfx::state::MASK_ALL,
+            Some(gfx::preset::blend::ALPHA),
+        ),
         scissor: gfx::Scissor = (),
     }
 }
 
+/// Color encoding of the render target, derived from its format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// The target stores colors as they are written (e.g. `gfx::format::Rgba8`). ImGui colors
+    /// are written and blended unchanged.
+    Linear,
+    /// The target encodes the written colors to sRGB (e.g. `gfx::format::Srgba8`). Vertex colors
+    /// are converted to linear before drawing, so that blending happens in linear space and the
+    /// UI looks the same as with `Linear`. The font atlas stays a linear texture, because it
+    /// only stores coverage in its alpha channel.
+    Srgb,
+}
+
+impl ColorSpace {
+    fn of_format(format: Format) -> ColorSpace {
+        match format.1 {
+            ChannelType::Srgb => ColorSpace::Srgb,
+            _ => ColorSpace::Linear,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Shaders {
     GlSl400, // OpenGL 4.0+
@@ -53,6 +115,39 @@ pub enum Shaders {
 }
 
 impl Shaders {
+    /// Chooses the shaders matching an OpenGL version description.
+    ///
+    /// The description can be a `GL_VERSION` or `GL_SHADING_LANGUAGE_VERSION` string (e.g.
+    /// `"4.5.0 NVIDIA 390.48"`, `"OpenGL ES 3.0 Mesa 17.3.6"` or `"OpenGL ES GLSL ES 1.00"`), or
+    /// anything else containing a `major.minor` version number, with `ES` marking an embedded
+    /// profile. The oldest desktop shaders are chosen if no version number is found.
+    pub fn detect(description: &str) -> Shaders {
+        use Shaders::*;
+        let is_embedded = description.split_whitespace().any(|word| word == "ES");
+        let (major, minor) = parse_version(description).unwrap_or((0, 0));
+        if is_embedded {
+            if major >= 3 { GlSlEs300 } else { GlSlEs100 }
+        } else if major >= 4 {
+            GlSl400
+        } else if major == 3 || (major == 1 && minor >= 30) {
+            // OpenGL 3.x, or a GLSL version string such as "1.30" or "1.50"
+            GlSl130
+        } else {
+            GlSl110
+        }
+    }
+    /// Returns the next shaders to try if these can't be used, or `None` if these are the
+    /// oldest ones of their profile.
+    pub fn fallback(self) -> Option<Shaders> {
+        use Shaders::*;
+        match self {
+            GlSl400 => Some(GlSl130),
+            GlSl130 => Some(GlSl110),
+            GlSl110 => None,
+            GlSlEs300 => Some(GlSlEs100),
+            GlSlEs100 => None,
+        }
+    }
     fn get_program_code(self) -> (&'static [u8], &'static [u8]) {
         use Shaders::*;
         match self {
@@ -80,24 +175,94 @@ impl Shaders {
     }
 }
 
+fn parse_version(description: &str) -> Option<(u32, u32)> {
+    description
+        .split(|c: char| !c.is_digit(10) && c != '.')
+        .filter_map(|token| {
+            let mut parts = token.split('.').map(|part| part.parse().ok());
+            match (parts.next(), parts.next()) {
+                (Some(Some(major)), Some(Some(minor))) => Some((major, minor)),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+/// A texture as drawn by the renderer: a shader resource view and the sampler to sample it with.
+pub type Texture<R> = (ShaderResourceView<R, [f32; 4]>, Sampler<R>);
+
+fn create_font_texture<R: Resources, F: Factory<R>>(
+    imgui: &mut ImGui,
+    factory: &mut F,
+) -> RendererResult<ShaderResourceView<R, [f32; 4]>> {
+    let (_, texture) = imgui.prepare_texture(|handle| {
+        factory.create_texture_immutable_u8::<gfx::format::Rgba8>(
+            gfx::texture::Kind::D2(
+                handle.width as u16,
+                handle.height as u16,
+                gfx::texture::AaMode::Single,
+            ),
+            &[handle.pixels],
+        )
+    })?;
+    Ok(texture)
+}
+
 pub struct Renderer<R: Resources> {
+    shaders: Shaders,
+    out_format: Format,
     bundle: Bundle<R, pipe::Data<R>>,
-    index_buffer: Buffer<R, u16>,
+    // One vertex buffer per batch of the frame
+    vertex_buffers: Vec<Buffer<R, ImDrawVert>>,
+    index_buffer: Buffer<R, ImDrawIdx>,
+    textures: Textures<Texture<R>>,
+    font_texture_id: TextureId,
+    frame: FrameBuffers,
 }
 
 impl<R: Resources> Renderer<R> {
-    pub fn init<F: Factory<R>>(
+    /// Initializes the renderer.
+    ///
+    /// If the pipeline can't be created with the given shaders, the older versions returned by
+    /// `Shaders::fallback` are tried in turn.
+    ///
+    /// The color space is chosen from the format of the render target, so rendering into e.g.
+    /// `gfx::format::Srgba8` is gamma-correct.
+    ///
+    /// If the device doesn't support a base vertex for indexed draw calls (e.g. OpenGL before
+    /// 3.2 and OpenGL ES), the indices are offset on the CPU instead.
+    pub fn init<F: Factory<R>, T: RenderFormat>(
         imgui: &mut ImGui,
         factory: &mut F,
         shaders: Shaders,
-        out: RenderTargetView<R, gfx::format::Rgba8>,
+        out: RenderTargetView<R, T>,
     ) -> RendererResult<Renderer<R>> {
-        let (vs_code, ps_code) = shaders.get_program_code();
-        let pso = factory.create_pipeline_simple(
-            vs_code,
-            ps_code,
-            pipe::new(),
-        )?;
+        let out_format = T::get_format();
+        let mut tried = Vec::new();
+        let mut next = Some(shaders);
+        let (shaders, pso) = loop {
+            let shaders = match next {
+                Some(shaders) => shaders,
+                None => return Err(RendererError::NoCompatibleShaders(tried)),
+            };
+            let (vs_code, ps_code) = shaders.get_program_code();
+            let init = pipe::Init {
+                out: (
+                    "Target0",
+                    out_format,
+                    gfx::state::MASK_ALL,
+                    Some(gfx::preset::blend::ALPHA),
+                ),
+                ..pipe::new()
+            };
+            match factory.create_pipeline_simple(vs_code, ps_code, init) {
+                Ok(pso) => break (shaders, pso),
+                Err(e) => {
+                    tried.push((shaders, e));
+                    next = shaders.fallback();
+                }
+            }
+        };
         let vertex_buffer = factory.create_buffer::<ImDrawVert>(
             256,
             gfx::buffer::Role::Vertex,
@@ -110,28 +275,20 @@ impl<R: Resources> Renderer<R> {
             gfx::memory::Usage::Dynamic,
             Bind::empty(),
         )?;
-        let (_, texture) = imgui.prepare_texture(|handle| {
-            factory.create_texture_immutable_u8::<gfx::format::Rgba8>(
-                gfx::texture::Kind::D2(
-                    handle.width as u16,
-                    handle.height as u16,
-                    gfx::texture::AaMode::Single,
-                ),
-                &[handle.pixels],
-            )
-        })?;
-        // TODO: set texture id in imgui
-        let sampler = factory.create_sampler_linear();
+        let font_texture = (create_font_texture(imgui, factory)?, factory.create_sampler_linear());
+        let mut textures = Textures::new();
+        let font_texture_id = textures.insert(font_texture.clone());
+        imgui.set_texture_id(font_texture_id.id());
         let data = pipe::Data {
-            vertex_buffer: vertex_buffer,
+            vertex_buffer: vertex_buffer.clone(),
             matrix: [
                 [0.0, 0.0, 0.0, 0.0],
                 [0.0, 0.0, 0.0, 0.0],
                 [0.0, 0.0, -1.0, 0.0],
                 [-1.0, 1.0, 0.0, 1.0],
             ],
-            tex: (texture, sampler),
-            out: out,
+            tex: font_texture,
+            out: out.raw().clone(),
             scissor: Rect {
                 x: 0,
                 y: 0,
@@ -146,14 +303,60 @@ impl<R: Resources> Renderer<R> {
             instances: None,
             buffer: index_buffer.clone().into_index_buffer(factory),
         };
+        let mut frame = FrameBuffers::default();
+        frame.set_linearize_colors(ColorSpace::of_format(out_format) == ColorSpace::Srgb);
+        // gfx_device_gl silently drops the base vertex if it isn't supported
+        frame.set_rebase_indices(!factory.get_capabilities().vertex_base_supported);
         Ok(Renderer {
+            shaders: shaders,
+            out_format: out_format,
             bundle: Bundle::new(slice, pso, data),
+            vertex_buffers: vec![vertex_buffer],
             index_buffer: index_buffer,
+            textures: textures,
+            font_texture_id: font_texture_id,
+            frame: frame,
         })
     }
-    pub fn update_render_target(&mut self, out: RenderTargetView<R, gfx::format::Rgba8>) {
-        self.bundle.data.out = out;
+    /// Rebuilds the font atlas and uploads it again, e.g. after fonts have been added or
+    /// resized with `ImGui::fonts_mut`.
+    pub fn reload_font_texture<F: Factory<R>>(
+        &mut self,
+        imgui: &mut ImGui,
+        factory: &mut F,
+    ) -> RendererResult<()> {
+        let texture = create_font_texture(imgui, factory)?;
+        let sampler = match self.textures.get(self.font_texture_id) {
+            Some(&(_, ref sampler)) => sampler.clone(),
+            None => factory.create_sampler_linear(),
+        };
+        self.textures.replace(self.font_texture_id, (texture, sampler));
+        imgui.set_texture_id(self.font_texture_id.id());
+        Ok(())
+    }
+    /// Returns the textures which can be drawn with `Ui::image` and the other image widgets,
+    /// for adding textures and getting their ids. The font texture is in the table too.
+    pub fn textures(&mut self) -> &mut Textures<Texture<R>> { &mut self.textures }
+    /// Returns the shaders that were chosen when initializing the renderer.
+    pub fn shaders(&self) -> Shaders { self.shaders }
+    /// Returns the color space of the render target.
+    pub fn color_space(&self) -> ColorSpace { ColorSpace::of_format(self.out_format) }
+    /// Replaces the render target, e.g. after the window has been resized.
+    ///
+    /// # Panics
+    /// Panics if the format of the new render target differs from the one given to `init`.
+    pub fn update_render_target<T: RenderFormat>(&mut self, out: RenderTargetView<R, T>) {
+        assert_eq!(
+            T::get_format(),
+            self.out_format,
+            "Render target format differs from the one the renderer was initialized with"
+        );
+        self.bundle.data.out = out.raw().clone();
     }
+    /// Renders the frame with the encoder.
+    ///
+    /// Draw callbacks get the encoder as `DrawCallbackContext::target`, which can be downcast
+    /// to `gfx::Encoder<R, C>`.
     pub fn render<'a, F: Factory<R>, C: CommandBuffer<R>>(
         &mut self,
         ui: Ui<'a>,
@@ -161,6 +364,7 @@ impl<R: Resources> Renderer<R> {
         encoder: &mut Encoder<R, C>,
     ) -> RendererResult<()> {
         let (width, height) = ui.imgui().display_size();
+        let (scale_width, scale_height) = ui.imgui().display_framebuffer_scale();
 
         if width == 0.0 || height == 0.0 {
             return Ok(());
@@ -172,43 +376,53 @@ impl<R: Resources> Renderer<R> {
             [-1.0, 1.0, 0.0, 1.0],
         ];
 
-        ui.render(|ui, draw_list| {
-            self.render_draw_list(ui, factory, encoder, &draw_list)
-        })
-    }
-    fn render_draw_list<'a, F: Factory<R>, C: CommandBuffer<R>>(
-        &mut self,
-        ui: &'a Ui<'a>,
-        factory: &mut F,
-        encoder: &mut Encoder<R, C>,
-        draw_list: &DrawList<'a>,
-    ) -> RendererResult<()> {
-        let (scale_width, scale_height) = ui.imgui().display_framebuffer_scale();
-
-        self.bundle.slice.start = 0;
-        for cmd in draw_list.cmd_buffer {
-            // TODO: check cmd.texture_id
+        {
+            let frame = &mut self.frame;
+            frame.clear();
+            ui.render_draw_data::<_, RendererError>(|ui, mut draw_data| {
+                draw_data.scale_clip_rects((scale_width, scale_height));
+                for draw_list in draw_data.draw_lists() {
+                    frame.push_draw_list(&draw_list, |cmd| ui.take_draw_callback(cmd));
+                }
+                Ok(())
+            })?;
+        }
 
-            self.upload_vertex_buffer(
-                factory,
-                encoder,
-                draw_list.vtx_buffer,
-            )?;
-            self.upload_index_buffer(
-                factory,
-                encoder,
-                draw_list.idx_buffer,
-            )?;
+        self.upload_vertex_buffer(factory, encoder)?;
+        self.upload_index_buffer(factory, encoder)?;
 
-            self.bundle.slice.end = self.bundle.slice.start + cmd.elem_count;
+        // The clip rectangles have been scaled to framebuffer pixels
+        for cmd in &mut self.frame.cmds {
+            let clip_rect = cmd.clip_rect;
+            if let Some(ref mut callback) = cmd.callback {
+                callback.run(&mut DrawCallbackContext {
+                    clip_rect: ImVec4::new(
+                        clip_rect.x / scale_width,
+                        clip_rect.y / scale_height,
+                        clip_rect.z / scale_width,
+                        clip_rect.w / scale_height,
+                    ),
+                    display_size: ImVec2::new(width, height),
+                    framebuffer_scale: ImVec2::new(scale_width, scale_height),
+                    target: Some(&mut *encoder as &mut Any),
+                });
+                continue;
+            }
+            match self.textures.get(cmd.texture_id) {
+                Some(texture) => self.bundle.data.tex = texture.clone(),
+                None => return Err(RendererError::BadTexture(cmd.texture_id)),
+            }
+            self.bundle.data.vertex_buffer = self.vertex_buffers[cmd.batch].clone();
+            self.bundle.slice.start = cmd.idx_offset;
+            self.bundle.slice.end = cmd.idx_offset + cmd.elem_count;
+            self.bundle.slice.base_vertex = cmd.base_vertex;
             self.bundle.data.scissor = Rect {
-                x: (cmd.clip_rect.x * scale_width) as u16,
-                y: (cmd.clip_rect.y * scale_height) as u16,
-                w: ((cmd.clip_rect.z - cmd.clip_rect.x).abs() * scale_width) as u16,
-                h: ((cmd.clip_rect.w - cmd.clip_rect.y).abs() * scale_height) as u16,
+                x: clip_rect.x as u16,
+                y: clip_rect.y as u16,
+                w: (clip_rect.z - clip_rect.x).abs() as u16,
+                h: (clip_rect.w - clip_rect.y).abs() as u16,
             };
             self.bundle.encode(encoder);
-            self.bundle.slice.start = self.bundle.slice.end;
         }
         Ok(())
     }
@@ -216,31 +430,36 @@ impl<R: Resources> Renderer<R> {
         &mut self,
         factory: &mut F,
         encoder: &mut Encoder<R, C>,
-        vtx_buffer: &[ImDrawVert],
     ) -> RendererResult<()> {
-        if self.bundle.data.vertex_buffer.len() < vtx_buffer.len() {
-            self.bundle.data.vertex_buffer = factory.create_buffer::<ImDrawVert>(
-                vtx_buffer.len(),
-                gfx::buffer::Role::Vertex,
-                gfx::memory::Usage::Dynamic,
-                Bind::empty(),
-            )?;
+        for (i, batch) in self.frame.batches.iter().enumerate() {
+            let vtx_buffer = &self.frame.vtx[batch.clone()];
+            let capacity = self.vertex_buffers.get(i).map_or(0, |buffer| buffer.len());
+            if capacity < vtx_buffer.len() {
+                let buffer = factory.create_buffer::<ImDrawVert>(
+                    grow_capacity(capacity, vtx_buffer.len()),
+                    gfx::buffer::Role::Vertex,
+                    gfx::memory::Usage::Dynamic,
+                    Bind::empty(),
+                )?;
+                if i < self.vertex_buffers.len() {
+                    self.vertex_buffers[i] = buffer;
+                } else {
+                    self.vertex_buffers.push(buffer);
+                }
+            }
+            encoder.update_buffer(&self.vertex_buffers[i], vtx_buffer, 0)?;
         }
-        Ok(encoder.update_buffer(
-            &self.bundle.data.vertex_buffer,
-            vtx_buffer,
-            0,
-        )?)
+        Ok(())
     }
     fn upload_index_buffer<F: Factory<R>, C: CommandBuffer<R>>(
         &mut self,
         factory: &mut F,
         encoder: &mut Encoder<R, C>,
-        idx_buffer: &[ImDrawIdx],
     ) -> RendererResult<()> {
+        let idx_buffer = &self.frame.idx;
         if self.index_buffer.len() < idx_buffer.len() {
             self.index_buffer = factory.create_buffer::<ImDrawIdx>(
-                idx_buffer.len(),
+                grow_capacity(self.index_buffer.len(), idx_buffer.len()),
                 gfx::buffer::Role::Index,
                 gfx::memory::Usage::Dynamic,
                 Bind::empty(),
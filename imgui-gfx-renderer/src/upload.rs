@@ -0,0 +1,107 @@
+use imgui::{DrawCallback, DrawList, ImDrawCmd, ImDrawIdx, ImDrawVert, ImVec4, TextureId};
+use imgui::render_util::{linearize_color, srgb_to_linear_table};
+use std::mem;
+use std::ops::Range;
+
+/// A draw command translated into offsets of the frame-wide buffers.
+#[derive(Debug)]
+pub struct FrameCmd {
+    pub idx_offset: u32,
+    pub elem_count: u32,
+    /// Offset added to the indices at draw time, relative to the start of the batch.
+    pub base_vertex: u32,
+    /// Index of the vertex batch the command draws from.
+    pub batch: usize,
+    pub clip_rect: ImVec4,
+    pub texture_id: TextureId,
+    /// Closure run instead of drawing, for commands added with `WindowDrawList::add_callback`.
+    pub callback: Option<DrawCallback>,
+}
+
+/// Vertices, indices and commands of all draw lists of a frame, packed so that they can be
+/// uploaded with a single buffer update each, or one per batch for the vertices.
+#[derive(Default)]
+pub struct FrameBuffers {
+    pub vtx: Vec<ImDrawVert>,
+    pub idx: Vec<ImDrawIdx>,
+    pub cmds: Vec<FrameCmd>,
+    /// Ranges of `vtx` which are uploaded to separate vertex buffers. There is a single batch
+    /// unless the indices are rebased.
+    pub batches: Vec<Range<usize>>,
+    rebase_indices: bool,
+    srgb_to_linear: Option<[u8; 256]>,
+}
+
+impl FrameBuffers {
+    pub fn clear(&mut self) {
+        self.vtx.clear();
+        self.idx.clear();
+        self.cmds.clear();
+        self.batches.clear();
+    }
+    /// Enables/disables adding the offset of each draw list to its indices, for backends
+    /// which ignore the base vertex of draw calls. The vertices are then split into batches
+    /// small enough for the rebased indices to fit into `ImDrawIdx`, and the base vertex of
+    /// every command is 0.
+    pub fn set_rebase_indices(&mut self, value: bool) { self.rebase_indices = value; }
+    /// Enables/disables the conversion of vertex colors from sRGB to linear, which is needed
+    /// when rendering into a target that encodes linear colors to sRGB.
+    pub fn set_linearize_colors(&mut self, value: bool) {
+        self.srgb_to_linear = if value { Some(srgb_to_linear_table()) } else { None };
+    }
+    /// Appends a draw list. Unless they are rebased, indices are kept as-is and offset by the
+    /// list's base vertex at draw time, so they stay within the range of `ImDrawIdx`.
+    ///
+    /// `take_callback` returns the closure of a callback command, normally
+    /// `Ui::take_draw_callback`.
+    pub fn push_draw_list<F>(&mut self, draw_list: &DrawList, mut take_callback: F)
+    where
+        F: FnMut(&ImDrawCmd) -> Option<DrawCallback>,
+    {
+        let vtx_start = self.vtx.len();
+        let vtx_end = vtx_start + draw_list.vtx_buffer.len();
+        let new_batch = match self.batches.last() {
+            Some(batch) => {
+                self.rebase_indices && vtx_start > batch.start &&
+                    vtx_end - batch.start > ImDrawIdx::max_value() as usize
+            }
+            None => true,
+        };
+        if new_batch {
+            self.batches.push(vtx_start..vtx_start);
+        }
+        let batch = self.batches.len() - 1;
+        self.batches[batch].end = vtx_end;
+        let base_vertex = (vtx_start - self.batches[batch].start) as u32;
+        let mut idx_offset = self.idx.len() as u32;
+        self.vtx.extend_from_slice(draw_list.vtx_buffer);
+        if let Some(ref table) = self.srgb_to_linear {
+            for vtx in &mut self.vtx[vtx_start..] {
+                vtx.col = linearize_color(table, vtx.col);
+            }
+        }
+        let base_vertex = if self.rebase_indices {
+            // The batch is small enough for the sums to fit
+            let offset = base_vertex as ImDrawIdx;
+            self.idx.extend(draw_list.idx_buffer.iter().map(|&idx| idx + offset));
+            0
+        } else {
+            self.idx.extend_from_slice(draw_list.idx_buffer);
+            base_vertex
+        };
+        for cmd in draw_list.cmd_buffer {
+            self.cmds.push(FrameCmd {
+                idx_offset: idx_offset,
+                elem_count: cmd.elem_count,
+                base_vertex: base_vertex,
+                batch: batch,
+                clip_rect: cmd.clip_rect,
+                texture_id: TextureId::from(cmd.texture_id),
+                callback: take_callback(cmd),
+            });
+            idx_offset += cmd.elem_count;
+        }
+    }
+    pub fn vtx_bytes(&self) -> usize { self.vtx.len() * mem::size_of::<ImDrawVert>() }
+    pub fn idx_bytes(&self) -> usize { self.idx.len() * mem::size_of::<ImDrawIdx>() }
+}
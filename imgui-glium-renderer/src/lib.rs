@@ -2,17 +2,26 @@
 extern crate glium;
 extern crate imgui;
 
-use glium::{DrawError, GlObject, IndexBuffer, Program, Surface, Texture2d, VertexBuffer};
+use glium::{DrawError, IndexBuffer, Program, Surface, Texture2d, VertexBuffer};
 use glium::backend::{Context, Facade};
+use glium::framebuffer::{self, SimpleFrameBuffer};
 use glium::program;
 use glium::index::{self, PrimitiveType};
 use glium::texture;
 use glium::vertex;
-use imgui::{DrawList, ImDrawIdx, ImDrawVert, ImGui, Ui};
+use imgui::{DrawCallbackContext, ImDrawIdx, ImDrawVert, ImGui, ImVec2, ImVec4, TextureId,
+            Textures, Ui};
+use imgui::render_util::grow_capacity;
+use std::any::Any;
 use std::borrow::Cow;
 use std::fmt;
+use std::mem;
 use std::rc::Rc;
 
+use upload::FrameBuffers;
+
+mod upload;
+
 pub type RendererResult<T> = Result<T, RendererError>;
 
 #[derive(Clone, Debug)]
@@ -21,7 +30,10 @@ pub enum RendererError {
     Index(index::BufferCreationError),
     Program(program::ProgramChooserCreationError),
     Texture(texture::TextureCreationError),
+    Framebuffer(framebuffer::ValidationError),
     Draw(DrawError),
+    /// A draw command uses a texture id which isn't in the renderer's `Textures`.
+    BadTexture(TextureId),
 }
 
 impl fmt::Display for RendererError {
@@ -32,7 +44,9 @@ impl fmt::Display for RendererError {
             Index(_) => write!(f, "Index buffer creation failed"),
             Program(ref e) => write!(f, "Program creation failed: {}", e),
             Texture(_) => write!(f, "Texture creation failed"),
+            Framebuffer(_) => write!(f, "Framebuffer creation failed"),
             Draw(ref e) => write!(f, "Drawing failed: {}", e),
+            BadTexture(id) => write!(f, "No texture with id {}", id.id()),
         }
     }
 }
@@ -53,57 +67,225 @@ impl From<texture::TextureCreationError> for RendererError {
     fn from(e: texture::TextureCreationError) -> RendererError { RendererError::Texture(e) }
 }
 
+impl From<framebuffer::ValidationError> for RendererError {
+    fn from(e: framebuffer::ValidationError) -> RendererError { RendererError::Framebuffer(e) }
+}
+
 impl From<DrawError> for RendererError {
     fn from(e: DrawError) -> RendererError { RendererError::Draw(e) }
 }
 
+/// Counters of the buffer uploads done by the renderer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UploadStats {
+    /// Bytes written to the vertex and index buffers during the last frame.
+    pub last_frame_bytes: usize,
+    /// Bytes written to the vertex and index buffers since the renderer was created.
+    pub total_bytes: u64,
+    /// Number of times the vertex or index buffer had to be reallocated to grow.
+    pub buffer_reallocations: usize,
+}
+
+impl UploadStats {
+    fn record(&mut self, bytes: usize) {
+        self.last_frame_bytes += bytes;
+        self.total_bytes += bytes as u64;
+    }
+}
+
+/// Color encoding of the render target.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// The target stores colors as they are written (e.g. a plain RGBA8 framebuffer). ImGui
+    /// colors are written and blended unchanged. This is the default.
+    Linear,
+    /// The target is an sRGB framebuffer that encodes the written colors to sRGB. Vertex colors
+    /// are converted to linear before drawing, so that blending happens in linear space and the
+    /// UI looks the same as with `Linear`. The font atlas stays a linear texture, because it
+    /// only stores coverage in its alpha channel.
+    Srgb,
+}
+
+/// Size of the area the UI is rendered to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    /// Size of the area in points, i.e. the coordinate space of the UI.
+    pub display_size: (f32, f32),
+    /// Number of framebuffer pixels per point.
+    pub framebuffer_scale: (f32, f32),
+}
+
+impl Viewport {
+    /// Returns the viewport recorded in ImGui for the current frame, which normally matches the
+    /// window.
+    pub fn from_imgui(imgui: &ImGui) -> Viewport {
+        Viewport {
+            display_size: imgui.display_size(),
+            framebuffer_scale: imgui.display_framebuffer_scale(),
+        }
+    }
+    /// Returns a viewport covering the whole texture, with the framebuffer scale derived from
+    /// the texture dimensions and the given display size.
+    pub fn for_texture(texture: &Texture2d, display_size: (f32, f32)) -> Viewport {
+        let (width, height) = texture.dimensions();
+        Viewport {
+            display_size: display_size,
+            framebuffer_scale: (
+                if display_size.0 > 0.0 { width as f32 / display_size.0 } else { 0.0 },
+                if display_size.1 > 0.0 { height as f32 / display_size.1 } else { 0.0 },
+            ),
+        }
+    }
+}
+
 pub struct Renderer {
     ctx: Rc<Context>,
     device_objects: DeviceObjects,
+    frame: FrameBuffers,
+    color_space: ColorSpace,
 }
 
 impl Renderer {
     pub fn init<F: Facade>(imgui: &mut ImGui, ctx: &F) -> RendererResult<Renderer> {
-        let device_objects = try!(DeviceObjects::init(imgui, ctx));
+        Renderer::init_with_color_space(imgui, ctx, ColorSpace::Linear)
+    }
+
+    /// Initializes the renderer for targets using the given color space.
+    pub fn init_with_color_space<F: Facade>(
+        imgui: &mut ImGui,
+        ctx: &F,
+        color_space: ColorSpace,
+    ) -> RendererResult<Renderer> {
+        let device_objects = try!(DeviceObjects::init_with_color_space(imgui, ctx, color_space));
+        let mut frame = FrameBuffers::default();
+        frame.set_linearize_colors(color_space == ColorSpace::Srgb);
         Ok(Renderer {
             ctx: Rc::clone(ctx.get_context()),
             device_objects: device_objects,
+            frame: frame,
+            color_space: color_space,
         })
     }
 
+    /// Returns the color space of the targets the renderer draws to.
+    pub fn color_space(&self) -> ColorSpace { self.color_space }
+
+    /// Returns the textures which can be drawn with `Ui::image` and the other image widgets,
+    /// for adding textures and getting their ids.
+    pub fn textures(&mut self) -> &mut Textures<Texture2d> { self.device_objects.textures() }
+
+    /// Returns the upload counters, which can be used to verify that the buffers are reused
+    /// across frames.
+    pub fn upload_stats(&self) -> UploadStats { self.device_objects.upload_stats }
+
+    /// Rebuilds the font atlas and uploads it again, e.g. after fonts have been added or
+    /// resized with `ImGui::fonts_mut`.
+    pub fn reload_font_texture(&mut self, imgui: &mut ImGui) -> RendererResult<()> {
+        self.device_objects.reload_font_texture(imgui, &self.ctx)
+    }
+
+    /// Renders the frame into a surface. Draw callbacks get no target, see
+    /// `render_with_callback_target`.
     pub fn render<'a, S: Surface>(&mut self, surface: &mut S, ui: Ui<'a>) -> RendererResult<()> {
-        let _ = self.ctx.insert_debug_marker("imgui-rs: starting rendering");
-        let result = ui.render(|ui, draw_list| {
-            self.render_draw_list(surface, ui, &draw_list)
-        });
-        let _ = self.ctx.insert_debug_marker("imgui-rs: rendering finished");
-        result
+        let viewport = Viewport::from_imgui(ui.imgui());
+        self.render_to_viewport(surface, ui, viewport)
+    }
+
+    /// Renders the frame into a surface like `render`, passing the surface to draw callbacks as
+    /// `DrawCallbackContext::target`. The target can be downcast to the type of the surface,
+    /// e.g. `glium::Frame`, which therefore must be `'static`.
+    pub fn render_with_callback_target<'a, S>(
+        &mut self,
+        surface: &mut S,
+        ui: Ui<'a>,
+    ) -> RendererResult<()>
+    where
+        S: Surface + Any,
+    {
+        let viewport = Viewport::from_imgui(ui.imgui());
+        self.render_with_target(surface, ui, viewport, any_target)
+    }
+
+    /// Renders the frame into a user-owned texture, e.g. to show the UI on a surface of a 3D
+    /// scene or to post-process it.
+    ///
+    /// The viewport is used instead of the display size and framebuffer scale recorded in
+    /// ImGui. Following the OpenGL convention, the top of the UI ends up in the last row of the
+    /// texture.
+    pub fn render_to_texture<'a>(
+        &mut self,
+        texture: &Texture2d,
+        ui: Ui<'a>,
+        viewport: Viewport,
+    ) -> RendererResult<()> {
+        let ctx = Rc::clone(&self.ctx);
+        let mut framebuffer = try!(SimpleFrameBuffer::new(&ctx, texture));
+        self.render_with_target(&mut framebuffer, ui, viewport, no_target)
     }
 
-    fn render_draw_list<'a, S: Surface>(
+    /// Renders the frame into a surface using the given viewport instead of the display size
+    /// and framebuffer scale recorded in ImGui.
+    pub fn render_to_viewport<'a, S: Surface>(
         &mut self,
         surface: &mut S,
-        ui: &'a Ui<'a>,
-        draw_list: &DrawList<'a>,
+        ui: Ui<'a>,
+        viewport: Viewport,
     ) -> RendererResult<()> {
-        use glium::{Blend, DrawParameters, Rect};
-        use glium::uniforms::{MinifySamplerFilter, MagnifySamplerFilter};
+        self.render_with_target(surface, ui, viewport, no_target)
+    }
 
+    fn render_with_target<'a, S: Surface>(
+        &mut self,
+        surface: &mut S,
+        ui: Ui<'a>,
+        viewport: Viewport,
+        as_target: fn(&mut S) -> Option<&mut Any>,
+    ) -> RendererResult<()> {
+        let _ = self.ctx.insert_debug_marker("imgui-rs: starting rendering");
+        let (width, height) = viewport.display_size;
+        let (scale_width, scale_height) = viewport.framebuffer_scale;
+
+        {
+            let frame = &mut self.frame;
+            frame.clear();
+            try!(ui.render_draw_data::<_, RendererError>(|ui, mut draw_data| {
+                draw_data.scale_clip_rects((scale_width, scale_height));
+                for draw_list in draw_data.draw_lists() {
+                    frame.push_draw_list(&draw_list, |cmd| ui.take_draw_callback(cmd));
+                }
+                Ok(())
+            }));
+        }
+        self.device_objects.upload_stats.last_frame_bytes = 0;
         try!(self.device_objects.upload_vertex_buffer(
             &self.ctx,
-            draw_list.vtx_buffer,
+            &self.frame.vtx,
         ));
         try!(self.device_objects.upload_index_buffer(
             &self.ctx,
-            draw_list.idx_buffer,
+            &self.frame.idx,
         ));
 
-        let (width, height) = ui.imgui().display_size();
-        let (scale_width, scale_height) = ui.imgui().display_framebuffer_scale();
+        let result = if width == 0.0 || height == 0.0 {
+            Ok(())
+        } else {
+            self.render_frame(surface, as_target, width, height, scale_width, scale_height)
+        };
+        let _ = self.ctx.insert_debug_marker("imgui-rs: rendering finished");
+        result
+    }
 
-        if width == 0.0 || height == 0.0 {
-            return Ok(());
-        }
+    fn render_frame<S: Surface>(
+        &mut self,
+        surface: &mut S,
+        as_target: fn(&mut S) -> Option<&mut Any>,
+        width: f32,
+        height: f32,
+        scale_width: f32,
+        scale_height: f32,
+    ) -> RendererResult<()> {
+        use glium::{Blend, DrawParameters, Rect};
+        use glium::uniforms::{MinifySamplerFilter, MagnifySamplerFilter};
 
         let matrix = [
             [2.0 / width as f32, 0.0, 0.0, 0.0],
@@ -111,93 +293,135 @@ impl Renderer {
             [0.0, 0.0, -1.0, 0.0],
             [-1.0, 1.0, 0.0, 1.0],
         ];
-        let font_texture_id = self.device_objects.texture.get_id() as usize;
-
-        let mut idx_start = 0;
-        for cmd in draw_list.cmd_buffer {
-            // We don't support custom textures...yet!
-            assert!(cmd.texture_id as usize == font_texture_id);
-
-            let idx_end = idx_start + cmd.elem_count as usize;
+        // The clip rectangles have been scaled to framebuffer pixels
+        let framebuffer_height = height * scale_height;
+        for cmd in &mut self.frame.cmds {
+            let clip_rect = cmd.clip_rect;
+            if let Some(ref mut callback) = cmd.callback {
+                callback.run(&mut DrawCallbackContext {
+                    clip_rect: ImVec4::new(
+                        clip_rect.x / scale_width,
+                        clip_rect.y / scale_height,
+                        clip_rect.z / scale_width,
+                        clip_rect.w / scale_height,
+                    ),
+                    display_size: ImVec2::new(width, height),
+                    framebuffer_scale: ImVec2::new(scale_width, scale_height),
+                    target: as_target(surface),
+                });
+                continue;
+            }
+            let texture = match self.device_objects.textures.get(cmd.texture_id) {
+                Some(texture) => texture,
+                None => return Err(RendererError::BadTexture(cmd.texture_id)),
+            };
 
             try!(
                 surface.draw(
-                    &self.device_objects.vertex_buffer,
+                    self.device_objects
+                        .vertex_buffer
+                        .slice(cmd.vtx_start..cmd.vtx_end)
+                        .expect("Invalid vertex buffer range"),
                     &self.device_objects
                         .index_buffer
-                        .slice(idx_start..idx_end)
+                        .slice(cmd.idx_start..cmd.idx_end)
                         .expect("Invalid index buffer range"),
                     &self.device_objects.program,
                     &uniform! {
                           matrix: matrix,
-                          tex: self.device_objects.texture.sampled()
+                          tex: texture.sampled()
                               .magnify_filter(MagnifySamplerFilter::Nearest)
                               .minify_filter(MinifySamplerFilter::Nearest),
                       },
                     &DrawParameters {
                         blend: Blend::alpha_blending(),
                         scissor: Some(Rect {
-                            left: (cmd.clip_rect.x * scale_width) as u32,
-                            bottom: ((height - cmd.clip_rect.w) * scale_height) as u32,
-                            width: ((cmd.clip_rect.z - cmd.clip_rect.x) * scale_width) as u32,
-                            height: ((cmd.clip_rect.w - cmd.clip_rect.y) * scale_height) as u32,
+                            left: clip_rect.x as u32,
+                            bottom: (framebuffer_height - clip_rect.w) as u32,
+                            width: (clip_rect.z - clip_rect.x) as u32,
+                            height: (clip_rect.w - clip_rect.y) as u32,
                         }),
                         ..DrawParameters::default()
                     },
                 )
             );
-
-            idx_start = idx_end;
         }
 
         Ok(())
     }
 }
 
+fn any_target<S: Any>(surface: &mut S) -> Option<&mut Any> { Some(surface) }
+
+fn no_target<S>(_: &mut S) -> Option<&mut Any> { None }
+
 pub struct DeviceObjects {
     vertex_buffer: VertexBuffer<ImDrawVert>,
     index_buffer: IndexBuffer<ImDrawIdx>,
     program: Program,
-    texture: Texture2d,
+    textures: Textures<Texture2d>,
+    font_texture_id: TextureId,
+    upload_stats: UploadStats,
 }
 
 fn compile_default_program<F: Facade>(
     ctx: &F,
+    outputs_srgb: bool,
 ) -> Result<Program, program::ProgramChooserCreationError> {
     program!(
         ctx,
         400 => {
             vertex: include_str!("shader/glsl_400.vert"),
             fragment: include_str!("shader/glsl_400.frag"),
-            outputs_srgb: true,
+            outputs_srgb: outputs_srgb,
         },
         130 => {
             vertex: include_str!("shader/glsl_130.vert"),
             fragment: include_str!("shader/glsl_130.frag"),
-            outputs_srgb: true,
+            outputs_srgb: outputs_srgb,
         },
         110 => {
             vertex: include_str!("shader/glsl_110.vert"),
             fragment: include_str!("shader/glsl_110.frag"),
-            outputs_srgb: true,
+            outputs_srgb: outputs_srgb,
         },
         300 es => {
             vertex: include_str!("shader/glsles_300.vert"),
             fragment: include_str!("shader/glsles_300.frag"),
-            outputs_srgb: true,
+            outputs_srgb: outputs_srgb,
         },
         100 es => {
             vertex: include_str!("shader/glsles_100.vert"),
             fragment: include_str!("shader/glsles_100.frag"),
-            outputs_srgb: true,
+            outputs_srgb: outputs_srgb,
         },
     )
 }
 
+fn upload_font_texture<F: Facade>(im_gui: &mut ImGui, ctx: &F) -> RendererResult<Texture2d> {
+    use glium::texture::{ClientFormat, RawImage2d};
+
+    let texture = try!(im_gui.prepare_texture(|handle| {
+        let data = RawImage2d {
+            data: Cow::Borrowed(handle.pixels),
+            width: handle.width,
+            height: handle.height,
+            format: ClientFormat::U8U8U8U8,
+        };
+        Texture2d::new(ctx, data)
+    }));
+    Ok(texture)
+}
+
 impl DeviceObjects {
     pub fn init<F: Facade>(im_gui: &mut ImGui, ctx: &F) -> RendererResult<DeviceObjects> {
-        use glium::texture::{ClientFormat, RawImage2d};
-
+        DeviceObjects::init_with_color_space(im_gui, ctx, ColorSpace::Linear)
+    }
+    pub fn init_with_color_space<F: Facade>(
+        im_gui: &mut ImGui,
+        ctx: &F,
+        color_space: ColorSpace,
+    ) -> RendererResult<DeviceObjects> {
         let vertex_buffer = try!(VertexBuffer::empty_dynamic(ctx, 0));
         let index_buffer = try!(IndexBuffer::empty_dynamic(
             ctx,
@@ -205,61 +429,86 @@ impl DeviceObjects {
             0,
         ));
 
-        let program = try!(compile_default_program(ctx));
-        let texture = try!(im_gui.prepare_texture(|handle| {
-            let data = RawImage2d {
-                data: Cow::Borrowed(handle.pixels),
-                width: handle.width,
-                height: handle.height,
-                format: ClientFormat::U8U8U8U8,
-            };
-            Texture2d::new(ctx, data)
-        }));
-        im_gui.set_texture_id(texture.get_id() as usize);
+        // With sRGB targets glium only enables the framebuffer's sRGB encoding if the program
+        // doesn't claim to output sRGB colors itself
+        let outputs_srgb = color_space == ColorSpace::Linear;
+        let program = try!(compile_default_program(ctx, outputs_srgb));
+        let mut textures = Textures::new();
+        let font_texture_id = textures.insert(try!(upload_font_texture(im_gui, ctx)));
+        im_gui.set_texture_id(font_texture_id.id());
 
         Ok(DeviceObjects {
             vertex_buffer: vertex_buffer,
             index_buffer: index_buffer,
             program: program,
-            texture: texture,
+            textures: textures,
+            font_texture_id: font_texture_id,
+            upload_stats: UploadStats::default(),
         })
     }
+    /// Rebuilds the font atlas if needed and replaces the font texture.
+    pub fn reload_font_texture<F: Facade>(
+        &mut self,
+        im_gui: &mut ImGui,
+        ctx: &F,
+    ) -> RendererResult<()> {
+        let texture = try!(upload_font_texture(im_gui, ctx));
+        self.textures.replace(self.font_texture_id, texture);
+        im_gui.set_texture_id(self.font_texture_id.id());
+        Ok(())
+    }
+    /// Returns the textures which can be drawn by the draw commands, including the font
+    /// texture.
+    pub fn textures(&mut self) -> &mut Textures<Texture2d> { &mut self.textures }
+    /// Writes the vertices to the start of the vertex buffer, which is reused until it has to
+    /// grow.
     pub fn upload_vertex_buffer<F: Facade>(
         &mut self,
         ctx: &F,
         vtx_buffer: &[ImDrawVert],
     ) -> RendererResult<()> {
-        self.vertex_buffer.invalidate();
+        if self.vertex_buffer.len() < vtx_buffer.len() {
+            let capacity = grow_capacity(self.vertex_buffer.len(), vtx_buffer.len());
+            self.vertex_buffer = try!(VertexBuffer::empty_dynamic(ctx, capacity));
+            self.upload_stats.buffer_reallocations += 1;
+            let _ = ctx.get_context().insert_debug_marker(&format!(
+                "imgui-rs: resized vertex buffer to {} bytes",
+                self.vertex_buffer.get_size()
+            ));
+        } else {
+            self.vertex_buffer.invalidate();
+        }
         if let Some(slice) = self.vertex_buffer.slice_mut(0..vtx_buffer.len()) {
             slice.write(vtx_buffer);
-            return Ok(());
         }
-        self.vertex_buffer = try!(VertexBuffer::dynamic(ctx, vtx_buffer));
-        let _ = ctx.get_context().insert_debug_marker(&format!(
-            "imgui-rs: resized vertex buffer to {} bytes",
-            self.vertex_buffer.get_size()
-        ));
+        self.upload_stats.record(vtx_buffer.len() * mem::size_of::<ImDrawVert>());
         Ok(())
     }
+    /// Writes the indices to the start of the index buffer, which is reused until it has to grow.
     pub fn upload_index_buffer<F: Facade>(
         &mut self,
         ctx: &F,
         idx_buffer: &[ImDrawIdx],
     ) -> RendererResult<()> {
-        self.index_buffer.invalidate();
+        if self.index_buffer.len() < idx_buffer.len() {
+            let capacity = grow_capacity(self.index_buffer.len(), idx_buffer.len());
+            self.index_buffer = try!(IndexBuffer::empty_dynamic(
+                ctx,
+                PrimitiveType::TrianglesList,
+                capacity,
+            ));
+            self.upload_stats.buffer_reallocations += 1;
+            let _ = ctx.get_context().insert_debug_marker(&format!(
+                "imgui-rs: resized index buffer to {} bytes",
+                self.index_buffer.get_size()
+            ));
+        } else {
+            self.index_buffer.invalidate();
+        }
         if let Some(slice) = self.index_buffer.slice_mut(0..idx_buffer.len()) {
             slice.write(idx_buffer);
-            return Ok(());
         }
-        self.index_buffer = try!(IndexBuffer::dynamic(
-            ctx,
-            PrimitiveType::TrianglesList,
-            idx_buffer,
-        ));
-        let _ = ctx.get_context().insert_debug_marker(&format!(
-            "imgui-rs: resized index buffer to {} bytes",
-            self.index_buffer.get_size()
-        ));
+        self.upload_stats.record(idx_buffer.len() * mem::size_of::<ImDrawIdx>());
         Ok(())
     }
 }
@@ -0,0 +1,71 @@
+use imgui::{DrawCallback, DrawList, ImDrawCmd, ImDrawIdx, ImDrawVert, ImVec4, TextureId};
+use imgui::render_util::{linearize_color, srgb_to_linear_table};
+
+/// A draw command translated into ranges of the frame-wide buffers.
+#[derive(Debug)]
+pub struct FrameCmd {
+    pub vtx_start: usize,
+    pub vtx_end: usize,
+    pub idx_start: usize,
+    pub idx_end: usize,
+    pub clip_rect: ImVec4,
+    pub texture_id: TextureId,
+    /// Closure run instead of drawing, for commands added with `WindowDrawList::add_callback`.
+    pub callback: Option<DrawCallback>,
+}
+
+/// Vertices, indices and commands of all draw lists of a frame, packed so that they can be
+/// uploaded with a single buffer write each.
+#[derive(Default)]
+pub struct FrameBuffers {
+    pub vtx: Vec<ImDrawVert>,
+    pub idx: Vec<ImDrawIdx>,
+    pub cmds: Vec<FrameCmd>,
+    srgb_to_linear: Option<[u8; 256]>,
+}
+
+impl FrameBuffers {
+    pub fn clear(&mut self) {
+        self.vtx.clear();
+        self.idx.clear();
+        self.cmds.clear();
+    }
+    /// Enables/disables the conversion of vertex colors from sRGB to linear, which is needed
+    /// when rendering into a target that encodes linear colors to sRGB.
+    pub fn set_linearize_colors(&mut self, value: bool) {
+        self.srgb_to_linear = if value { Some(srgb_to_linear_table()) } else { None };
+    }
+    /// Appends a draw list. Indices are kept as-is and the list's vertices are bound as a slice
+    /// at draw time, so they stay within the range of `ImDrawIdx`.
+    ///
+    /// `take_callback` returns the closure of a callback command, normally
+    /// `Ui::take_draw_callback`.
+    pub fn push_draw_list<F>(&mut self, draw_list: &DrawList, mut take_callback: F)
+    where
+        F: FnMut(&ImDrawCmd) -> Option<DrawCallback>,
+    {
+        let vtx_start = self.vtx.len();
+        let mut idx_start = self.idx.len();
+        self.vtx.extend_from_slice(draw_list.vtx_buffer);
+        if let Some(ref table) = self.srgb_to_linear {
+            for vtx in &mut self.vtx[vtx_start..] {
+                vtx.col = linearize_color(table, vtx.col);
+            }
+        }
+        self.idx.extend_from_slice(draw_list.idx_buffer);
+        let vtx_end = self.vtx.len();
+        for cmd in draw_list.cmd_buffer {
+            let idx_end = idx_start + cmd.elem_count as usize;
+            self.cmds.push(FrameCmd {
+                vtx_start: vtx_start,
+                vtx_end: vtx_end,
+                idx_start: idx_start,
+                idx_end: idx_end,
+                clip_rect: cmd.clip_rect,
+                texture_id: TextureId::from(cmd.texture_id),
+                callback: take_callback(cmd),
+            });
+            idx_start = idx_end;
+        }
+    }
+}
@@ -1,7 +1,13 @@
 extern crate cc;
 
+use std::env;
+
 fn main() {
-    cc::Build::new()
+    let mut build = cc::Build::new();
+    if env::var_os("CARGO_FEATURE_IDX32").is_some() {
+        build.define("ImDrawIdx", "unsigned int");
+    }
+    build
         .cpp(true)
         .file("third-party/cimgui/cimgui/cimgui.cpp")
         .file("third-party/cimgui/cimgui/fontAtlas.cpp")
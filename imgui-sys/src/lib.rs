@@ -13,6 +13,7 @@ extern crate glium;
 use std::convert::From;
 use std::mem;
 use std::os::raw::{c_char, c_float, c_int, c_short, c_uchar, c_uint, c_ushort, c_void};
+use std::ptr;
 use std::slice;
 
 #[cfg(feature = "gfx")]
@@ -324,6 +325,22 @@ bitflags!(
     }
 );
 
+bitflags!(
+    /// Flags for the corners to round of rectangles in draw lists
+    #[repr(C)]
+    pub struct ImDrawCornerFlags: c_int {
+        const TopLeft  = 1;
+        const TopRight = 1 << 1;
+        const BotRight = 1 << 2;
+        const BotLeft  = 1 << 3;
+        const Top      = ImDrawCornerFlags::TopLeft.bits | ImDrawCornerFlags::TopRight.bits;
+        const Bot      = ImDrawCornerFlags::BotLeft.bits | ImDrawCornerFlags::BotRight.bits;
+        const Left     = ImDrawCornerFlags::TopLeft.bits | ImDrawCornerFlags::BotLeft.bits;
+        const Right    = ImDrawCornerFlags::TopRight.bits | ImDrawCornerFlags::BotRight.bits;
+        const All      = 0xF;
+    }
+);
+
 pub type ImGuiTextEditCallback = Option<
     extern "C" fn(data: *mut ImGuiTextEditCallbackData) -> c_int,
 >;
@@ -660,7 +677,11 @@ pub struct ImDrawCmd {
 }
 
 /// Vertex index
+#[cfg(not(feature = "idx32"))]
 pub type ImDrawIdx = c_ushort;
+/// Vertex index, 32 bits wide with the `idx32` feature
+#[cfg(feature = "idx32")]
+pub type ImDrawIdx = c_uint;
 
 /// A single vertex
 #[repr(C)]
@@ -692,8 +713,8 @@ pub struct ImDrawList {
     clip_rect_stack: ImVector<ImVec4>,
     texture_id_stack: ImVector<ImTextureID>,
     path: ImVector<ImVec2>,
-    channels_current: c_int,
-    channels_count: c_int,
+    pub channels_current: c_int,
+    pub channels_count: c_int,
     channels: ImVector<ImDrawChannel>,
 }
 
@@ -736,19 +757,43 @@ pub struct ImFontConfig {
     dst_font: *mut ImFont,
 }
 
+impl Default for ImFontConfig {
+    /// Returns the same configuration as the ImFontConfig default constructor
+    fn default() -> ImFontConfig {
+        ImFontConfig {
+            font_data: ptr::null_mut(),
+            font_data_size: 0,
+            font_data_owned_by_atlas: true,
+            font_no: 0,
+            size_pixels: 0.0,
+            oversample_h: 3,
+            oversample_v: 1,
+            pixel_snap_h: false,
+            glyph_extra_spacing: ImVec2::zero(),
+            glyph_offset: ImVec2::zero(),
+            glyph_ranges: ptr::null(),
+            merge_mode: false,
+            rasterizer_flags: 0,
+            rasterizer_multiply: 1.0,
+            name: [0; 32],
+            dst_font: ptr::null_mut(),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default)]
 pub struct ImFontGlyph {
-    codepoint: ImWchar,
-    advance_x: c_float,
-    x0: c_float,
-    y0: c_float,
-    x1: c_float,
-    y1: c_float,
-    u0: c_float,
-    v0: c_float,
-    u1: c_float,
-    v1: c_float,
+    pub codepoint: ImWchar,
+    pub advance_x: c_float,
+    pub x0: c_float,
+    pub y0: c_float,
+    pub x1: c_float,
+    pub y1: c_float,
+    pub u0: c_float,
+    pub v0: c_float,
+    pub u1: c_float,
+    pub v1: c_float,
 }
 
 #[repr(C)]
@@ -777,7 +822,7 @@ pub struct ImFontAtlas {
     tex_uv_white_pixel: ImVec2,
     fonts: ImVector<*mut ImFont>,
     custom_rects: ImVector<CustomRect>,
-    config_data: ImVector<ImFontConfig>,
+    pub config_data: ImVector<ImFontConfig>,
     custom_rect_ids: [c_int; 1],
 }
 
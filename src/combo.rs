@@ -0,0 +1,172 @@
+#![warn(missing_docs)]
+use sys;
+use std::any::Any;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+use super::{ImStr, Ui};
+
+/// Builder for a combo box over a slice of items of any type, which are labeled by a closure.
+///
+/// # Example
+/// ```rust,no_run
+/// # use imgui::*;
+/// # use std::borrow::Cow;
+/// # let mut imgui = ImGui::init();
+/// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+/// struct Mode {
+///     name: ImString,
+///     width: u32,
+/// }
+/// # let modes: Vec<Mode> = Vec::new();
+/// let mut selected = None;
+/// ui.combo_items(im_str!("Video mode"), &modes, |mode| Cow::Borrowed(&mode.name))
+///     .preview_text(im_str!("Choose a mode"))
+///     .build(&mut selected);
+/// ```
+#[must_use]
+pub struct Combo<'ui, 'p, T: 'p, L> {
+    label: &'p ImStr,
+    items: &'p [T],
+    item_label: L,
+    preview_text: Option<&'p ImStr>,
+    height_in_items: i32,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p, T: 'p, L> Combo<'ui, 'p, T, L>
+where
+    L: Fn(&T) -> Cow<ImStr>,
+{
+    /// Creates a combo box builder.
+    pub fn new(_: &Ui<'ui>, label: &'p ImStr, items: &'p [T], item_label: L) -> Self {
+        Combo {
+            label: label,
+            items: items,
+            item_label: item_label,
+            preview_text: None,
+            height_in_items: -1,
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the text shown in the closed combo box instead of the label of the selected item.
+    /// It is also shown if no item is selected.
+    #[inline]
+    pub fn preview_text(mut self, preview_text: &'p ImStr) -> Self {
+        self.preview_text = Some(preview_text);
+        self
+    }
+    /// Sets the maximum number of items shown in the popup without scrolling.
+    #[inline]
+    pub fn height_in_items(mut self, height_in_items: i32) -> Self {
+        self.height_in_items = height_in_items;
+        self
+    }
+    /// Builds the combo box, with `current` as the index of the selected item. Returns true
+    /// if an item was selected in the popup.
+    pub fn build(self, current: &mut Option<usize>) -> bool {
+        let len = self.items.len();
+        let selected = match *current {
+            Some(index) if index < len => Some(index),
+            _ => None,
+        };
+        // ImGui only shows a preview for a valid item, and queries its label before the popup
+        // items. Without a selection, item 0 stands in for the preview and is unselected again.
+        let (mut current_item, preview_text, unselect) = match (selected, self.preview_text) {
+            (Some(index), preview_text) => (index as c_int, preview_text, false),
+            (None, Some(preview_text)) if len > 0 => (0, Some(preview_text), true),
+            (None, _) => (-1, None, false),
+        };
+        let current_item_ptr: *mut c_int = &mut current_item;
+        let mut data = GetterData {
+            items: self.items,
+            item_label: &self.item_label,
+            preview_text: preview_text,
+            unselect: if unselect { Some(current_item_ptr) } else { None },
+            text: None,
+            panic: None,
+        };
+        let changed = unsafe {
+            sys::igCombo3(
+                self.label.as_ptr(),
+                current_item_ptr,
+                items_getter::<T, L>,
+                &mut data as *mut GetterData<T, L> as *mut c_void,
+                len as c_int,
+                self.height_in_items,
+            )
+        };
+        if let Some(payload) = data.panic {
+            panic::resume_unwind(payload);
+        }
+        if changed {
+            *current = Some(current_item as usize);
+        }
+        changed
+    }
+    /// Builds the combo box, with the item equal to `value` selected. Returns true if an item
+    /// was selected in the popup, after storing a clone of it in `value`.
+    pub fn build_value(self, value: &mut T) -> bool
+    where
+        T: Clone + PartialEq,
+    {
+        let items = self.items;
+        let mut current = items.iter().position(|item| item == value);
+        let changed = self.build(&mut current);
+        if changed {
+            if let Some(index) = current {
+                *value = items[index].clone();
+            }
+        }
+        changed
+    }
+}
+
+struct GetterData<'p, T: 'p, L: 'p> {
+    items: &'p [T],
+    item_label: &'p L,
+    // Returned for the first label, which is the preview
+    preview_text: Option<&'p ImStr>,
+    // Current item which is reset after the preview, so that no item is highlighted
+    unselect: Option<*mut c_int>,
+    // Keeps the last label alive until ImGui has used it
+    text: Option<Cow<'p, ImStr>>,
+    panic: Option<Box<Any + Send>>,
+}
+
+extern "C" fn items_getter<T, L>(
+    data: *mut c_void,
+    idx: c_int,
+    out_text: *mut *const c_char,
+) -> bool
+where
+    L: Fn(&T) -> Cow<ImStr>,
+{
+    let data = unsafe { &mut *(data as *mut GetterData<T, L>) };
+    if data.panic.is_some() {
+        return false;
+    }
+    if let Some(preview_text) = data.preview_text.take() {
+        if let Some(current_item) = data.unselect.take() {
+            unsafe { *current_item = -1 };
+        }
+        unsafe { *out_text = preview_text.as_ptr() };
+        return true;
+    }
+    let items = data.items;
+    let item_label = data.item_label;
+    // Panics must not unwind into ImGui, so they are resumed after igCombo3 has returned
+    match panic::catch_unwind(AssertUnwindSafe(|| item_label(&items[idx as usize]))) {
+        Ok(text) => {
+            unsafe { *out_text = text.as_ptr() };
+            data.text = Some(text);
+            true
+        }
+        Err(payload) => {
+            data.panic = Some(payload);
+            false
+        }
+    }
+}
@@ -0,0 +1,219 @@
+#![warn(missing_docs)]
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use super::{ImStr, ImString};
+
+/// A printf format for the value of a slider or drag widget, which has been checked to be safe
+/// to pass to ImGui.
+///
+/// ImGui formats the values of all these widgets as doubles, including the integer ones. The
+/// format may therefore contain at most one conversion, which must be a float conversion (`f`,
+/// `F`, `e`, `E`, `g`, `G`, `a` or `A`) with optional flags, width and precision. Widths and
+/// precisions have at most two digits. `%%` shows a percent sign.
+///
+/// # Example
+/// ```rust
+/// # use imgui::*;
+/// assert!(DisplayFormat::new("%.2f dB").is_ok());
+/// assert!(DisplayFormat::new("%s").is_err());
+/// assert_eq!(DisplayFormat::literal("100%").as_im_str().to_str(), "100%%");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayFormat(ImString);
+
+impl DisplayFormat {
+    /// Checks that `format` is a safe display format.
+    pub fn new(format: &str) -> Result<DisplayFormat, DisplayFormatError> {
+        try!(validate(format));
+        Ok(DisplayFormat(ImString::new(format)))
+    }
+    /// Returns a format which shows `text` as is instead of the value, e.g. text formatted in
+    /// Rust.
+    pub fn literal(text: &str) -> DisplayFormat {
+        DisplayFormat(ImString::new(text.replace('%', "%%")))
+    }
+    /// Returns the format as an `ImStr`.
+    pub fn as_im_str(&self) -> &ImStr { &self.0 }
+    /// Returns `format` if it is a safe display format, and otherwise a format which shows it
+    /// literally.
+    pub(crate) fn sanitize<'a>(format: &'a ImStr) -> Cow<'a, ImStr> {
+        if validate(format.to_str()).is_ok() {
+            Cow::Borrowed(format)
+        } else {
+            Cow::Owned(DisplayFormat::literal(format.to_str()).0)
+        }
+    }
+    pub(crate) fn into_cow<'a>(self) -> Cow<'a, ImStr> { Cow::Owned(self.0) }
+}
+
+impl AsRef<ImStr> for DisplayFormat {
+    fn as_ref(&self) -> &ImStr { &self.0 }
+}
+
+/// Reason why a display format was rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisplayFormatError {
+    /// The conversion starting at the given byte offset isn't a float conversion, or uses a
+    /// length modifier, a `*` width or precision, or more than two digits.
+    InvalidConversion(usize),
+    /// The format has more than one conversion.
+    TooManyConversions,
+    /// The format contains a nul byte.
+    NulByte,
+}
+
+impl fmt::Display for DisplayFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DisplayFormatError::InvalidConversion(offset) => {
+                write!(f, "Invalid conversion at byte {} of the display format", offset)
+            }
+            DisplayFormatError::TooManyConversions => {
+                write!(f, "The display format has several conversions")
+            }
+            DisplayFormatError::NulByte => write!(f, "The display format contains a nul byte"),
+        }
+    }
+}
+
+impl Error for DisplayFormatError {
+    fn description(&self) -> &str {
+        match *self {
+            DisplayFormatError::InvalidConversion(_) => "Invalid conversion in the display format",
+            DisplayFormatError::TooManyConversions => "The display format has several conversions",
+            DisplayFormatError::NulByte => "The display format contains a nul byte",
+        }
+    }
+}
+
+pub(crate) fn default_int_format<'a>() -> Cow<'a, ImStr> {
+    Cow::Borrowed(unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.0f\0") })
+}
+
+pub(crate) fn default_float_format<'a>() -> Cow<'a, ImStr> {
+    Cow::Borrowed(unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.3f\0") })
+}
+
+fn validate(format: &str) -> Result<(), DisplayFormatError> {
+    if format.contains('\0') {
+        return Err(DisplayFormatError::NulByte);
+    }
+    let bytes = format.as_bytes();
+    let mut conversions = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if bytes.get(i) == Some(&b'%') {
+            i += 1;
+            continue;
+        }
+        while i < bytes.len() && b"-+ #0".contains(&bytes[i]) {
+            i += 1;
+        }
+        i = try!(skip_digits(bytes, i, start));
+        if bytes.get(i) == Some(&b'.') {
+            i = try!(skip_digits(bytes, i + 1, start));
+        }
+        match bytes.get(i) {
+            Some(c) if b"fFeEgGaA".contains(c) => i += 1,
+            _ => return Err(DisplayFormatError::InvalidConversion(start)),
+        }
+        conversions += 1;
+        if conversions > 1 {
+            return Err(DisplayFormatError::TooManyConversions);
+        }
+    }
+    Ok(())
+}
+
+fn skip_digits(bytes: &[u8], start: usize, conversion: usize) -> Result<usize, DisplayFormatError> {
+    let mut i = start;
+    while i < bytes.len() && b'0' <= bytes[i] && bytes[i] <= b'9' {
+        i += 1;
+    }
+    if i - start > 2 {
+        Err(DisplayFormatError::InvalidConversion(conversion))
+    } else {
+        Ok(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_float_conversions() {
+        for format in &["%.3f", "%5.2f", "%%", "x = %+08.2e", "%G ms", "%-#a", "100 %% %.0f"] {
+            assert_eq!(validate(format), Ok(()), "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_accepts_literals() {
+        for format in &["", "Volume", "50%%"] {
+            assert_eq!(validate(format), Ok(()), "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_rejects_other_conversions() {
+        for format in &["%s", "%d", "%n", "%lf", "%*f", "%.*f"] {
+            assert_eq!(
+                validate(format),
+                Err(DisplayFormatError::InvalidConversion(0)),
+                "{}",
+                format
+            );
+        }
+        assert_eq!(validate("x = %s"), Err(DisplayFormatError::InvalidConversion(4)));
+    }
+
+    #[test]
+    fn test_rejects_long_numbers() {
+        assert_eq!(validate("%.123f"), Err(DisplayFormatError::InvalidConversion(0)));
+        assert_eq!(validate("%100f"), Err(DisplayFormatError::InvalidConversion(0)));
+        assert_eq!(validate("%99.99f"), Ok(()));
+    }
+
+    #[test]
+    fn test_rejects_trailing_percent() {
+        assert_eq!(validate("%"), Err(DisplayFormatError::InvalidConversion(0)));
+        assert_eq!(validate("%.2f %"), Err(DisplayFormatError::InvalidConversion(5)));
+    }
+
+    #[test]
+    fn test_rejects_several_conversions() {
+        assert_eq!(validate("%f %f"), Err(DisplayFormatError::TooManyConversions));
+    }
+
+    #[test]
+    fn test_rejects_nul_byte() {
+        assert_eq!(DisplayFormat::new("%f\0"), Err(DisplayFormatError::NulByte));
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(DisplayFormat::literal("100%").as_im_str().to_str(), "100%%");
+        assert_eq!(DisplayFormat::literal("%s").as_im_str().to_str(), "%%s");
+        assert_eq!(DisplayFormat::literal("%%").as_im_str().to_str(), "%%%%");
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let valid = ImString::new("%.2f dB");
+        match DisplayFormat::sanitize(&valid) {
+            Cow::Borrowed(format) => assert_eq!(format.to_str(), "%.2f dB"),
+            Cow::Owned(_) => panic!("A valid format was escaped"),
+        }
+        let stray = ImString::new("50% %s");
+        assert_eq!(DisplayFormat::sanitize(&stray).to_str(), "50%% %%s");
+    }
+}
@@ -0,0 +1,485 @@
+use sys;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::ptr;
+
+use super::display_format::{default_float_format, default_int_format};
+use super::{DisplayFormat, ImStr, Ui};
+
+/// Builder for a drag widget editing an integer.
+#[must_use]
+pub struct DragInt<'ui, 'p> {
+    label: &'p ImStr,
+    value: &'p mut i32,
+    speed: f32,
+    min: i32,
+    max: i32,
+    display_format: Cow<'p, ImStr>,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> DragInt<'ui, 'p> {
+    pub fn new(_: &Ui<'ui>, label: &'p ImStr, value: &'p mut i32) -> Self {
+        DragInt {
+            label: label,
+            value: value,
+            speed: 1.0,
+            min: 0,
+            max: 0,
+            display_format: default_int_format(),
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the value change per pixel of mouse movement.
+    #[inline]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    /// Sets the minimum value. The value is only clamped if the minimum is less than the
+    /// maximum.
+    #[inline]
+    pub fn min(mut self, min: i32) -> Self {
+        self.min = min;
+        self
+    }
+    /// Sets the maximum value.
+    #[inline]
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = max;
+        self
+    }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes. While the value is dragged, ImGui truncates it instead of rounding it.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(i32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igDragInt(
+                self.label.as_ptr(),
+                self.value,
+                self.speed,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+            )
+        }
+    }
+}
+
+macro_rules! impl_drag_intn {
+    ($DragIntN:ident, $N:expr, $igDragIntN:ident) => {
+        /// Builder for a drag widget editing integers.
+        #[must_use]
+        pub struct $DragIntN<'ui, 'p> {
+            label: &'p ImStr,
+            value: &'p mut [i32; $N],
+            speed: f32,
+            min: i32,
+            max: i32,
+            display_format: Cow<'p, ImStr>,
+            _phantom: PhantomData<&'ui Ui<'ui>>,
+        }
+
+        impl<'ui, 'p> $DragIntN<'ui, 'p> {
+            pub fn new(_: &Ui<'ui>, label: &'p ImStr, value: &'p mut [i32; $N]) -> Self {
+                $DragIntN {
+                    label: label,
+                    value: value,
+                    speed: 1.0,
+                    min: 0,
+                    max: 0,
+                    display_format: default_int_format(),
+                    _phantom: PhantomData,
+                }
+            }
+            #[inline]
+            pub fn speed(mut self, speed: f32) -> Self {
+                self.speed = speed;
+                self
+            }
+            #[inline]
+            pub fn min(mut self, min: i32) -> Self {
+                self.min = min;
+                self
+            }
+            #[inline]
+            pub fn max(mut self, max: i32) -> Self {
+                self.max = max;
+                self
+            }
+            /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+            /// shown literally.
+            #[inline]
+            pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+                self.display_format = DisplayFormat::sanitize(display_format);
+                self
+            }
+            pub fn build(self) -> bool {
+                unsafe {
+                    sys::$igDragIntN(
+                        self.label.as_ptr(),
+                        self.value.as_mut_ptr(),
+                        self.speed,
+                        self.min,
+                        self.max,
+                        self.display_format.as_ptr())
+                }
+            }
+        }
+    }
+}
+
+impl_drag_intn!(DragInt2, 2, igDragInt2);
+impl_drag_intn!(DragInt3, 3, igDragInt3);
+impl_drag_intn!(DragInt4, 4, igDragInt4);
+
+/// Builder for a drag widget editing a range of integers as two values, where the minimum can't
+/// be dragged above the maximum and vice versa.
+#[must_use]
+pub struct DragIntRange2<'ui, 'p> {
+    label: &'p ImStr,
+    current_min: &'p mut i32,
+    current_max: &'p mut i32,
+    speed: f32,
+    min: i32,
+    max: i32,
+    display_format: Cow<'p, ImStr>,
+    display_format_max: Option<Cow<'p, ImStr>>,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> DragIntRange2<'ui, 'p> {
+    pub fn new(
+        _: &Ui<'ui>,
+        label: &'p ImStr,
+        current_min: &'p mut i32,
+        current_max: &'p mut i32,
+    ) -> Self {
+        DragIntRange2 {
+            label: label,
+            current_min: current_min,
+            current_max: current_max,
+            speed: 1.0,
+            min: 0,
+            max: 0,
+            display_format: default_int_format(),
+            display_format_max: None,
+            _phantom: PhantomData,
+        }
+    }
+    #[inline]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    #[inline]
+    pub fn min(mut self, min: i32) -> Self {
+        self.min = min;
+        self
+    }
+    #[inline]
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = max;
+        self
+    }
+    /// Sets the display format of both values, unless `display_format_max` is set.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Sets the display format of the maximum value.
+    #[inline]
+    pub fn display_format_max(mut self, display_format_max: &'p ImStr) -> Self {
+        self.display_format_max = Some(DisplayFormat::sanitize(display_format_max));
+        self
+    }
+    /// Shows the values formatted by `f` instead of using printf formats. The text is
+    /// formatted when the widget is built, so it shows the values from before this frame's
+    /// changes. While the value is dragged, ImGui truncates them instead of rounding them.
+    #[inline]
+    pub fn display_format_with<F: Fn(i32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.current_min)).into_cow();
+        self.display_format_max =
+            Some(DisplayFormat::literal(&f(*self.current_max)).into_cow());
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igDragIntRange2(
+                self.label.as_ptr(),
+                self.current_min,
+                self.current_max,
+                self.speed,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+                self.display_format_max.as_ref().map_or(ptr::null(), |f| f.as_ptr()),
+            )
+        }
+    }
+}
+
+/// Builder for a drag widget editing a float.
+#[must_use]
+pub struct DragFloat<'ui, 'p> {
+    label: &'p ImStr,
+    value: &'p mut f32,
+    speed: f32,
+    min: f32,
+    max: f32,
+    display_format: Cow<'p, ImStr>,
+    power: f32,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> DragFloat<'ui, 'p> {
+    pub fn new(_: &Ui<'ui>, label: &'p ImStr, value: &'p mut f32) -> Self {
+        DragFloat {
+            label: label,
+            value: value,
+            speed: 1.0,
+            min: 0.0,
+            max: 0.0,
+            display_format: default_float_format(),
+            power: 1.0,
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the value change per pixel of mouse movement.
+    #[inline]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    /// Sets the minimum value. The value is only clamped if the minimum is less than the
+    /// maximum.
+    #[inline]
+    pub fn min(mut self, min: f32) -> Self {
+        self.min = min;
+        self
+    }
+    /// Sets the maximum value.
+    #[inline]
+    pub fn max(mut self, max: f32) -> Self {
+        self.max = max;
+        self
+    }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(f32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
+        self
+    }
+    /// Sets the power of the exponential curve used when dragging, for finer control near
+    /// the minimum.
+    #[inline]
+    pub fn power(mut self, power: f32) -> Self {
+        self.power = power;
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igDragFloat(
+                self.label.as_ptr(),
+                self.value,
+                self.speed,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+                self.power,
+            )
+        }
+    }
+}
+
+macro_rules! impl_drag_floatn {
+    ($DragFloatN:ident, $N:expr, $igDragFloatN:ident) => {
+        /// Builder for a drag widget editing floats.
+        #[must_use]
+        pub struct $DragFloatN<'ui, 'p> {
+            label: &'p ImStr,
+            value: &'p mut [f32; $N],
+            speed: f32,
+            min: f32,
+            max: f32,
+            display_format: Cow<'p, ImStr>,
+            power: f32,
+            _phantom: PhantomData<&'ui Ui<'ui>>,
+        }
+
+        impl<'ui, 'p> $DragFloatN<'ui, 'p> {
+            pub fn new(_: &Ui<'ui>, label: &'p ImStr, value: &'p mut [f32; $N]) -> Self {
+                $DragFloatN {
+                    label: label,
+                    value: value,
+                    speed: 1.0,
+                    min: 0.0,
+                    max: 0.0,
+                    display_format: default_float_format(),
+                    power: 1.0,
+                    _phantom: PhantomData,
+                }
+            }
+            #[inline]
+            pub fn speed(mut self, speed: f32) -> Self {
+                self.speed = speed;
+                self
+            }
+            #[inline]
+            pub fn min(mut self, min: f32) -> Self {
+                self.min = min;
+                self
+            }
+            #[inline]
+            pub fn max(mut self, max: f32) -> Self {
+                self.max = max;
+                self
+            }
+            /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+            /// shown literally.
+            #[inline]
+            pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+                self.display_format = DisplayFormat::sanitize(display_format);
+                self
+            }
+            #[inline]
+            pub fn power(mut self, power: f32) -> Self {
+                self.power = power;
+                self
+            }
+            pub fn build(self) -> bool {
+                unsafe {
+                    sys::$igDragFloatN(
+                        self.label.as_ptr(),
+                        self.value.as_mut_ptr(),
+                        self.speed,
+                        self.min,
+                        self.max,
+                        self.display_format.as_ptr(),
+                        self.power)
+                }
+            }
+        }
+    }
+}
+
+impl_drag_floatn!(DragFloat2, 2, igDragFloat2);
+impl_drag_floatn!(DragFloat3, 3, igDragFloat3);
+impl_drag_floatn!(DragFloat4, 4, igDragFloat4);
+
+/// Builder for a drag widget editing a range of floats as two values, where the minimum can't
+/// be dragged above the maximum and vice versa.
+#[must_use]
+pub struct DragFloatRange2<'ui, 'p> {
+    label: &'p ImStr,
+    current_min: &'p mut f32,
+    current_max: &'p mut f32,
+    speed: f32,
+    min: f32,
+    max: f32,
+    display_format: Cow<'p, ImStr>,
+    display_format_max: Option<Cow<'p, ImStr>>,
+    power: f32,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> DragFloatRange2<'ui, 'p> {
+    pub fn new(
+        _: &Ui<'ui>,
+        label: &'p ImStr,
+        current_min: &'p mut f32,
+        current_max: &'p mut f32,
+    ) -> Self {
+        DragFloatRange2 {
+            label: label,
+            current_min: current_min,
+            current_max: current_max,
+            speed: 1.0,
+            min: 0.0,
+            max: 0.0,
+            display_format: default_float_format(),
+            display_format_max: None,
+            power: 1.0,
+            _phantom: PhantomData,
+        }
+    }
+    #[inline]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    #[inline]
+    pub fn min(mut self, min: f32) -> Self {
+        self.min = min;
+        self
+    }
+    #[inline]
+    pub fn max(mut self, max: f32) -> Self {
+        self.max = max;
+        self
+    }
+    /// Sets the display format of both values, unless `display_format_max` is set.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Sets the display format of the maximum value.
+    #[inline]
+    pub fn display_format_max(mut self, display_format_max: &'p ImStr) -> Self {
+        self.display_format_max = Some(DisplayFormat::sanitize(display_format_max));
+        self
+    }
+    /// Shows the values formatted by `f` instead of using printf formats. The text is
+    /// formatted when the widget is built, so it shows the values from before this frame's
+    /// changes.
+    #[inline]
+    pub fn display_format_with<F: Fn(f32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.current_min)).into_cow();
+        self.display_format_max =
+            Some(DisplayFormat::literal(&f(*self.current_max)).into_cow());
+        self
+    }
+    #[inline]
+    pub fn power(mut self, power: f32) -> Self {
+        self.power = power;
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igDragFloatRange2(
+                self.label.as_ptr(),
+                self.current_min,
+                self.current_max,
+                self.speed,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+                self.display_format_max.as_ref().map_or(ptr::null(), |f| f.as_ptr()),
+                self.power,
+            )
+        }
+    }
+}
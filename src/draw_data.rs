@@ -0,0 +1,110 @@
+#![warn(missing_docs)]
+use sys;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use std::slice;
+
+use super::{DrawList, ImVec2};
+
+/// All draw lists of a frame in render order: the background draw list, the draw lists of
+/// the windows and the overlay draw list.
+///
+/// The draw lists can be post-processed before they are rendered.
+pub struct DrawData<'a> {
+    lists: Vec<*mut sys::ImDrawList>,
+    _phantom: PhantomData<&'a mut sys::ImDrawList>,
+}
+
+impl<'a> DrawData<'a> {
+    pub(crate) unsafe fn new(lists: Vec<*mut sys::ImDrawList>) -> DrawData<'a> {
+        DrawData {
+            lists: lists,
+            _phantom: PhantomData,
+        }
+    }
+    /// Returns the number of draw lists.
+    pub fn draw_lists_count(&self) -> usize { self.lists.len() }
+    /// Returns an iterator over the draw lists.
+    pub fn draw_lists<'d>(&'d self) -> DrawListIter<'d> {
+        DrawListIter {
+            lists: self.lists.iter(),
+            _phantom: PhantomData,
+        }
+    }
+    /// Returns the number of vertices in all draw lists.
+    pub fn total_vtx_count(&self) -> usize {
+        self.draw_lists().map(|list| list.vtx_buffer.len()).sum()
+    }
+    /// Returns the number of indices in all draw lists.
+    pub fn total_idx_count(&self) -> usize {
+        self.draw_lists().map(|list| list.idx_buffer.len()).sum()
+    }
+    /// Returns the number of draw commands in all draw lists.
+    pub fn total_cmd_count(&self) -> usize {
+        self.draw_lists().map(|list| list.cmd_buffer.len()).sum()
+    }
+    /// Multiplies the clip rectangles of all draw commands by `scale`, e.g. by the framebuffer
+    /// scale to get clip rectangles in framebuffer pixels.
+    pub fn scale_clip_rects<S: Into<ImVec2>>(&mut self, scale: S) {
+        let scale = scale.into();
+        unsafe { self.with_raw(|draw_data| sys::ImDrawData_ScaleClipRects(draw_data, scale)) }
+    }
+    /// Replaces the vertices of each draw list by the vertices referenced by its indices, in
+    /// index order, and clears the indices. For renderers which can't draw indexed triangles.
+    ///
+    /// The element counts of the draw commands then count vertices instead of indices.
+    pub fn deindex(&mut self) {
+        unsafe { self.with_raw(|draw_data| sys::ImDrawData_DeIndexAllBuffers(draw_data)) }
+    }
+    /// Merges adjacent draw commands which have the same texture and clip rectangle, so that
+    /// they are drawn with a single draw call. Callback commands are never merged.
+    pub fn merge_commands(&mut self) {
+        for &list in &self.lists {
+            unsafe {
+                let cmd_buffer = &mut (*list).cmd_buffer;
+                let cmds = slice::from_raw_parts_mut(cmd_buffer.data, cmd_buffer.size as usize);
+                let mut len = 0;
+                for i in 0..cmds.len() {
+                    if len > 0 && can_merge(&cmds[len - 1], &cmds[i]) {
+                        cmds[len - 1].elem_count += cmds[i].elem_count;
+                    } else {
+                        cmds.swap(len, i);
+                        len += 1;
+                    }
+                }
+                cmd_buffer.size = len as c_int;
+            }
+        }
+    }
+    unsafe fn with_raw<F: FnOnce(*mut sys::ImDrawData)>(&mut self, f: F) {
+        let mut draw_data = sys::ImDrawData {
+            valid: true,
+            cmd_lists: self.lists.as_mut_ptr(),
+            cmd_lists_count: self.lists.len() as c_int,
+            total_vtx_count: self.total_vtx_count() as c_int,
+            total_idx_count: self.total_idx_count() as c_int,
+        };
+        f(&mut draw_data);
+    }
+}
+
+fn can_merge(a: &sys::ImDrawCmd, b: &sys::ImDrawCmd) -> bool {
+    a.user_callback.is_none() && b.user_callback.is_none() && a.texture_id == b.texture_id &&
+        a.clip_rect == b.clip_rect
+}
+
+/// Iterator over the draw lists of a frame.
+pub struct DrawListIter<'a> {
+    lists: slice::Iter<'a, *mut sys::ImDrawList>,
+    _phantom: PhantomData<&'a sys::ImDrawList>,
+}
+
+impl<'a> Iterator for DrawListIter<'a> {
+    type Item = DrawList<'a>;
+    fn next(&mut self) -> Option<DrawList<'a>> {
+        self.lists.next().map(|&list| unsafe { DrawList::from_raw(list) })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.lists.size_hint() }
+}
+
+impl<'a> ExactSizeIterator for DrawListIter<'a> {}
@@ -0,0 +1,1544 @@
+#![warn(missing_docs)]
+use sys;
+use sys::{ImDrawCmd, ImDrawCornerFlags, ImDrawIdx, ImDrawVert, ImU32};
+use std::any::Any;
+use std::f32::consts::PI;
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::thread;
+
+use super::{FontId, ImGui, ImVec2, ImVec4, TextureId, Ui};
+
+/// A color packed into 32 bits, as used by draw lists.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImColor(ImU32);
+
+impl ImColor {
+    /// Creates a color from 8-bit red, green, blue and alpha components.
+    pub fn from_rgba_u8(r: u8, g: u8, b: u8, a: u8) -> ImColor {
+        ImColor((a as ImU32) << 24 | (b as ImU32) << 16 | (g as ImU32) << 8 | r as ImU32)
+    }
+    /// Creates a color from floating point red, green, blue and alpha components in the range
+    /// 0.0 to 1.0.
+    pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> ImColor {
+        fn to_u8(v: f32) -> u8 {
+            let v = if v < 0.0 { 0.0 } else if v > 1.0 { 1.0 } else { v };
+            (v * 255.0 + 0.5) as u8
+        }
+        ImColor::from_rgba_u8(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+    /// Creates a color from the packed representation used by ImGui.
+    pub fn from_u32(value: ImU32) -> ImColor { ImColor(value) }
+    /// Returns the packed representation used by ImGui.
+    pub fn to_u32(self) -> ImU32 { self.0 }
+}
+
+impl From<ImColor> for ImU32 {
+    fn from(color: ImColor) -> ImU32 { color.0 }
+}
+
+impl From<ImVec4> for ImColor {
+    fn from(v: ImVec4) -> ImColor { ImColor::from_rgba(v.x, v.y, v.z, v.w) }
+}
+
+impl From<[f32; 4]> for ImColor {
+    fn from(v: [f32; 4]) -> ImColor { ImColor::from_rgba(v[0], v[1], v[2], v[3]) }
+}
+
+impl From<(f32, f32, f32, f32)> for ImColor {
+    fn from(v: (f32, f32, f32, f32)) -> ImColor { ImColor::from_rgba(v.0, v.1, v.2, v.3) }
+}
+
+impl From<[f32; 3]> for ImColor {
+    fn from(v: [f32; 3]) -> ImColor { ImColor::from_rgba(v[0], v[1], v[2], 1.0) }
+}
+
+impl From<(f32, f32, f32)> for ImColor {
+    fn from(v: (f32, f32, f32)) -> ImColor { ImColor::from_rgba(v.0, v.1, v.2, 1.0) }
+}
+
+/// The draw list of the current window, for drawing custom shapes.
+///
+/// Positions are in screen coordinates, e.g. relative to `Ui::get_cursor_screen_pos`.
+///
+/// # Example
+/// ```rust,no_run
+/// # use imgui::*;
+/// # let mut imgui = ImGui::init();
+/// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+/// let draw_list = ui.get_window_draw_list();
+/// draw_list
+///     .add_rect((10.0, 10.0), (110.0, 60.0), [0.2, 0.6, 0.2])
+///     .filled(true)
+///     .rounding(4.0)
+///     .build();
+/// draw_list
+///     .add_line((10.0, 70.0), (110.0, 70.0), [1.0, 1.0, 1.0])
+///     .thickness(2.0)
+///     .build();
+/// ```
+pub struct WindowDrawList<'ui> {
+    draw_list: *mut sys::ImDrawList,
+    imgui: &'ui ImGui,
+}
+
+impl<'ui> WindowDrawList<'ui> {
+    pub(crate) fn new(ui: &Ui<'ui>) -> WindowDrawList<'ui> {
+        WindowDrawList::borrow(ui.imgui, unsafe { sys::igGetWindowDrawList() })
+    }
+    pub(crate) fn from_owned(ui: &Ui<'ui>, owned: &OwnedDrawList) -> WindowDrawList<'ui> {
+        WindowDrawList::borrow(ui.imgui, owned.raw())
+    }
+    fn borrow(imgui: &'ui ImGui, draw_list: *mut sys::ImDrawList) -> WindowDrawList<'ui> {
+        let mut in_use = imgui.draw_lists_in_use.borrow_mut();
+        assert!(
+            !in_use.contains(&draw_list),
+            "The draw list is already borrowed by another WindowDrawList"
+        );
+        in_use.push(draw_list);
+        WindowDrawList {
+            draw_list: draw_list,
+            imgui: imgui,
+        }
+    }
+    pub(crate) fn raw(&self) -> *mut sys::ImDrawList { self.draw_list }
+    /// Returns a line builder.
+    pub fn add_line<'dl, P1, P2, C>(&'dl self, p1: P1, p2: P2, color: C) -> Line<'dl>
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        Line::new(self, p1.into(), p2.into(), color.into())
+    }
+    /// Returns a rectangle builder for the rectangle between the upper left corner `p1` and the
+    /// lower right corner `p2`.
+    pub fn add_rect<'dl, P1, P2, C>(&'dl self, p1: P1, p2: P2, color: C) -> Rect<'dl>
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        Rect::new(self, p1.into(), p2.into(), color.into())
+    }
+    /// Draws a filled rectangle with a color for each corner, which are interpolated.
+    pub fn add_rect_filled_multicolor<P1, P2, C1, C2, C3, C4>(
+        &self,
+        p1: P1,
+        p2: P2,
+        col_upper_left: C1,
+        col_upper_right: C2,
+        col_bottom_right: C3,
+        col_bottom_left: C4,
+    ) where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        C1: Into<ImColor>,
+        C2: Into<ImColor>,
+        C3: Into<ImColor>,
+        C4: Into<ImColor>,
+    {
+        unsafe {
+            sys::ImDrawList_AddRectFilledMultiColor(
+                self.draw_list,
+                p1.into(),
+                p2.into(),
+                col_upper_left.into().to_u32(),
+                col_upper_right.into().to_u32(),
+                col_bottom_right.into().to_u32(),
+                col_bottom_left.into().to_u32(),
+            );
+        }
+    }
+    /// Returns a quadrilateral builder. The points are expected in clockwise order.
+    pub fn add_quad<'dl, C>(
+        &'dl self,
+        a: ImVec2,
+        b: ImVec2,
+        c: ImVec2,
+        d: ImVec2,
+        color: C,
+    ) -> Quad<'dl>
+    where
+        C: Into<ImColor>,
+    {
+        Quad::new(self, [a, b, c, d], color.into())
+    }
+    /// Returns a triangle builder. The points are expected in clockwise order.
+    pub fn add_triangle<'dl, C>(
+        &'dl self,
+        a: ImVec2,
+        b: ImVec2,
+        c: ImVec2,
+        color: C,
+    ) -> Triangle<'dl>
+    where
+        C: Into<ImColor>,
+    {
+        Triangle::new(self, [a, b, c], color.into())
+    }
+    /// Returns a circle builder.
+    pub fn add_circle<'dl, P, C>(&'dl self, center: P, radius: f32, color: C) -> Circle<'dl>
+    where
+        P: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        Circle::new(self, center.into(), radius, color.into())
+    }
+    /// Returns a text builder, which uses the current font by default.
+    pub fn add_text<'dl, 'p, P, C>(&'dl self, pos: P, color: C, text: &'p str) -> DrawText<'dl, 'p>
+    where
+        P: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        DrawText::new(self, pos.into(), color.into(), text)
+    }
+    /// Returns an image builder for the image between the upper left corner `p_min` and the
+    /// lower right corner `p_max`.
+    pub fn add_image<'dl, P1, P2>(
+        &'dl self,
+        texture_id: TextureId,
+        p_min: P1,
+        p_max: P2,
+    ) -> DrawImage<'dl>
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+    {
+        DrawImage::new(self, texture_id, p_min.into(), p_max.into())
+    }
+    /// Returns a builder for a line going through the points, or for the convex polygon with
+    /// these corners when filled.
+    pub fn add_polyline<'dl, 'p, C>(&'dl self, points: &'p [ImVec2], color: C) -> Polyline<'dl, 'p>
+    where
+        C: Into<ImColor>,
+    {
+        Polyline::new(self, points, color.into())
+    }
+    /// Returns a builder for a cubic bezier curve from `pos0` to `pos1`, with the control points
+    /// `cp0` and `cp1`.
+    pub fn add_bezier_curve<'dl, C>(
+        &'dl self,
+        pos0: ImVec2,
+        cp0: ImVec2,
+        cp1: ImVec2,
+        pos1: ImVec2,
+        color: C,
+    ) -> BezierCurve<'dl>
+    where
+        C: Into<ImColor>,
+    {
+        BezierCurve::new(self, [pos0, cp0, cp1, pos1], color.into())
+    }
+    /// Adds a closure which is run by the renderer when it reaches this point of the draw list,
+    /// e.g. to render a 3D viewport inside a window.
+    ///
+    /// The closure is kept until the next frame starts. Following shapes are drawn after the
+    /// closure has run.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// ui.get_window_draw_list().add_callback(|ctx| {
+    ///     let scissor = ctx.framebuffer_clip_rect();
+    ///     // Render the viewport into the scissor rectangle
+    /// });
+    /// ```
+    pub fn add_callback<F>(&self, callback: F)
+    where
+        F: for<'a, 'b> FnMut(&'a mut DrawCallbackContext<'b>) + 'static,
+    {
+        let mut callbacks = self.imgui.draw_callbacks.borrow_mut();
+        callbacks.push(Some(DrawCallback(Box::new(callback))));
+        // The index is stored instead of a pointer, so that a command can't outlive its closure
+        let index = callbacks.len() - 1;
+        unsafe {
+            sys::ImDrawList_AddCallback(
+                self.draw_list,
+                Some(draw_callback_marker),
+                index as *mut c_void,
+            );
+        }
+    }
+    /// Returns a builder for a shape made of lines, arcs and curves, which is drawn by
+    /// `Path::fill` or `Path::stroke`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// let draw_list = ui.get_window_draw_list();
+    /// // A pie slice
+    /// draw_list
+    ///     .path()
+    ///     .line_to((50.0, 50.0))
+    ///     .arc_to((50.0, 50.0), 40.0, 0.0, 1.5, 16)
+    ///     .fill([1.0, 0.5, 0.0]);
+    /// ```
+    pub fn path<'dl>(&'dl self) -> Path<'dl> { Path::new(self) }
+    /// Returns a writer for a mesh of at most `idx_count` indices and `vtx_count` vertices,
+    /// which form triangles drawn without the overhead of the shape builders.
+    ///
+    /// The mesh is drawn with the current texture, which is normally the font atlas. Solid
+    /// colors use `white_pixel_uv` as the texture coordinates of all vertices.
+    ///
+    /// # Panics
+    /// Panics if the draw list would end up with more vertices than `ImDrawIdx` can index, now
+    /// or when the mesh is added.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// # let samples = [0.5f32; 1000];
+    /// let draw_list = ui.get_window_draw_list();
+    /// let uv = draw_list.white_pixel_uv();
+    /// let color = ImColor::from([0.2, 0.8, 0.2]);
+    /// let mut mesh = draw_list.mesh((samples.len() as u32 - 1) * 6, samples.len() as u32 * 2);
+    /// // An area plot as a strip of quads
+    /// for (i, sample) in samples.iter().enumerate() {
+    ///     let x = 10.0 + i as f32 * 0.5;
+    ///     mesh.write_vertex((x, 110.0), uv, color);
+    ///     mesh.write_vertex((x, 110.0 - sample * 100.0), uv, color);
+    ///     if i > 0 {
+    ///         let base = (i as ImDrawIdx - 1) * 2;
+    ///         mesh.write_indices(&[base, base + 1, base + 3, base, base + 3, base + 2]);
+    ///     }
+    /// }
+    /// // The mesh is added to the draw list here
+    /// drop(mesh);
+    /// ```
+    pub fn mesh<'dl>(&'dl self, idx_count: u32, vtx_count: u32) -> MeshWriter<'dl> {
+        MeshWriter::new(self, idx_count, vtx_count)
+    }
+    /// Returns the texture coordinates of a white pixel in the font atlas, for drawing solid
+    /// colors in meshes.
+    pub fn white_pixel_uv(&self) -> ImVec2 {
+        let mut uv = ImVec2::zero();
+        unsafe { sys::igGetFontTexUvWhitePixel(&mut uv) }
+        uv
+    }
+    /// Splits the draw list into channels, which are merged in order when the returned guard
+    /// is dropped. Shapes are drawn through the guard into the current channel, so e.g.
+    /// backgrounds drawn into channel 0 after foregrounds drawn into channel 1 end up behind
+    /// them.
+    ///
+    /// ImGui can't split a draw list which is already split, so `ui.columns` must not be
+    /// called while the guard is alive.
+    ///
+    /// # Panics
+    /// Panics if `channels_count` is 0, or if the draw list is already split, e.g. by
+    /// `ui.columns`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// let mut draw_list = ui.get_window_draw_list();
+    /// {
+    ///     let mut channels = draw_list.channels_split(2);
+    ///     channels.set_current(1);
+    ///     channels.add_text((12.0, 12.0), [1.0, 1.0, 1.0], "Node").build();
+    ///     channels.set_current(0);
+    ///     channels
+    ///         .add_rect((10.0, 10.0), (60.0, 30.0), [0.3, 0.3, 0.3])
+    ///         .filled(true)
+    ///         .build();
+    /// } // The channels are merged here
+    /// ```
+    pub fn channels_split<'dl>(&'dl mut self, channels_count: u32) -> ChannelsSplit<'dl> {
+        ChannelsSplit::new(self, channels_count)
+    }
+    /// Runs a function after temporarily pushing a clip rectangle. Shapes drawn by the
+    /// function are clipped to the rectangle only.
+    pub fn with_clip_rect<P1, P2, F>(&self, min: P1, max: P2, f: F)
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        F: FnOnce(),
+    {
+        self.push_clip_rect(min.into(), max.into(), false, f);
+    }
+    /// Runs a function after temporarily pushing a clip rectangle, which is intersected with
+    /// the current clip rectangle.
+    pub fn with_clip_rect_intersect<P1, P2, F>(&self, min: P1, max: P2, f: F)
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        F: FnOnce(),
+    {
+        self.push_clip_rect(min.into(), max.into(), true, f);
+    }
+    fn push_clip_rect<F: FnOnce()>(&self, min: ImVec2, max: ImVec2, intersect: bool, f: F) {
+        unsafe { sys::ImDrawList_PushClipRect(self.draw_list, min, max, intersect) }
+        f();
+        unsafe { sys::ImDrawList_PopClipRect(self.draw_list) }
+    }
+}
+
+impl<'ui> Drop for WindowDrawList<'ui> {
+    fn drop(&mut self) {
+        let mut in_use = self.imgui.draw_lists_in_use.borrow_mut();
+        if let Some(index) = in_use.iter().position(|&list| list == self.draw_list) {
+            in_use.swap_remove(index);
+        }
+    }
+}
+
+/// Builder for a line.
+#[must_use]
+pub struct Line<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    p1: ImVec2,
+    p2: ImVec2,
+    color: ImColor,
+    thickness: f32,
+}
+
+impl<'dl> Line<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, p1: ImVec2, p2: ImVec2, color: ImColor) -> Self {
+        Line {
+            draw_list: draw_list,
+            p1: p1,
+            p2: p2,
+            color: color,
+            thickness: 1.0,
+        }
+    }
+    /// Sets the thickness of the line (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Draws the line.
+    pub fn build(self) {
+        unsafe {
+            sys::ImDrawList_AddLine(
+                self.draw_list.raw(),
+                self.p1,
+                self.p2,
+                self.color.to_u32(),
+                self.thickness,
+            );
+        }
+    }
+}
+
+/// Builder for a rectangle.
+#[must_use]
+pub struct Rect<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    p1: ImVec2,
+    p2: ImVec2,
+    color: ImColor,
+    rounding: f32,
+    rounding_corners: ImDrawCornerFlags,
+    thickness: f32,
+    filled: bool,
+}
+
+impl<'dl> Rect<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, p1: ImVec2, p2: ImVec2, color: ImColor) -> Self {
+        Rect {
+            draw_list: draw_list,
+            p1: p1,
+            p2: p2,
+            color: color,
+            rounding: 0.0,
+            rounding_corners: ImDrawCornerFlags::All,
+            thickness: 1.0,
+            filled: false,
+        }
+    }
+    /// Sets the radius of the rounded corners (default: 0.0, no rounding).
+    #[inline]
+    pub fn rounding(mut self, rounding: f32) -> Self {
+        self.rounding = rounding;
+        self
+    }
+    /// Sets which corners are rounded (default: all of them).
+    #[inline]
+    pub fn rounding_corners(mut self, rounding_corners: ImDrawCornerFlags) -> Self {
+        self.rounding_corners = rounding_corners;
+        self
+    }
+    /// Enables/disables rounding of the upper left corner (default: enabled).
+    #[inline]
+    pub fn round_top_left(mut self, value: bool) -> Self {
+        self.rounding_corners.set(ImDrawCornerFlags::TopLeft, value);
+        self
+    }
+    /// Enables/disables rounding of the upper right corner (default: enabled).
+    #[inline]
+    pub fn round_top_right(mut self, value: bool) -> Self {
+        self.rounding_corners.set(ImDrawCornerFlags::TopRight, value);
+        self
+    }
+    /// Enables/disables rounding of the lower left corner (default: enabled).
+    #[inline]
+    pub fn round_bot_left(mut self, value: bool) -> Self {
+        self.rounding_corners.set(ImDrawCornerFlags::BotLeft, value);
+        self
+    }
+    /// Enables/disables rounding of the lower right corner (default: enabled).
+    #[inline]
+    pub fn round_bot_right(mut self, value: bool) -> Self {
+        self.rounding_corners.set(ImDrawCornerFlags::BotRight, value);
+        self
+    }
+    /// Sets the thickness of the outline (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Enables/disables filling the rectangle instead of drawing its outline (default: false).
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+        self
+    }
+    /// Draws the rectangle.
+    pub fn build(self) {
+        unsafe {
+            if self.filled {
+                sys::ImDrawList_AddRectFilled(
+                    self.draw_list.raw(),
+                    self.p1,
+                    self.p2,
+                    self.color.to_u32(),
+                    self.rounding,
+                    self.rounding_corners.bits(),
+                );
+            } else {
+                sys::ImDrawList_AddRect(
+                    self.draw_list.raw(),
+                    self.p1,
+                    self.p2,
+                    self.color.to_u32(),
+                    self.rounding,
+                    self.rounding_corners.bits(),
+                    self.thickness,
+                );
+            }
+        }
+    }
+}
+
+/// Builder for a quadrilateral.
+#[must_use]
+pub struct Quad<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    points: [ImVec2; 4],
+    color: ImColor,
+    thickness: f32,
+    filled: bool,
+}
+
+impl<'dl> Quad<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, points: [ImVec2; 4], color: ImColor) -> Self {
+        Quad {
+            draw_list: draw_list,
+            points: points,
+            color: color,
+            thickness: 1.0,
+            filled: false,
+        }
+    }
+    /// Sets the thickness of the outline (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Enables/disables filling the quadrilateral instead of drawing its outline
+    /// (default: false).
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+        self
+    }
+    /// Draws the quadrilateral.
+    pub fn build(self) {
+        let (a, b, c, d) = (self.points[0], self.points[1], self.points[2], self.points[3]);
+        unsafe {
+            if self.filled {
+                sys::ImDrawList_AddQuadFilled(
+                    self.draw_list.raw(),
+                    a,
+                    b,
+                    c,
+                    d,
+                    self.color.to_u32(),
+                );
+            } else {
+                sys::ImDrawList_AddQuad(
+                    self.draw_list.raw(),
+                    a,
+                    b,
+                    c,
+                    d,
+                    self.color.to_u32(),
+                    self.thickness,
+                );
+            }
+        }
+    }
+}
+
+/// Builder for a triangle.
+#[must_use]
+pub struct Triangle<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    points: [ImVec2; 3],
+    color: ImColor,
+    thickness: f32,
+    filled: bool,
+}
+
+impl<'dl> Triangle<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, points: [ImVec2; 3], color: ImColor) -> Self {
+        Triangle {
+            draw_list: draw_list,
+            points: points,
+            color: color,
+            thickness: 1.0,
+            filled: false,
+        }
+    }
+    /// Sets the thickness of the outline (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Enables/disables filling the triangle instead of drawing its outline (default: false).
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+        self
+    }
+    /// Draws the triangle.
+    pub fn build(self) {
+        let (a, b, c) = (self.points[0], self.points[1], self.points[2]);
+        unsafe {
+            if self.filled {
+                sys::ImDrawList_AddTriangleFilled(
+                    self.draw_list.raw(),
+                    a,
+                    b,
+                    c,
+                    self.color.to_u32(),
+                );
+            } else {
+                sys::ImDrawList_AddTriangle(
+                    self.draw_list.raw(),
+                    a,
+                    b,
+                    c,
+                    self.color.to_u32(),
+                    self.thickness,
+                );
+            }
+        }
+    }
+}
+
+/// Builder for a circle.
+#[must_use]
+pub struct Circle<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    center: ImVec2,
+    radius: f32,
+    color: ImColor,
+    num_segments: u32,
+    thickness: f32,
+    filled: bool,
+}
+
+impl<'dl> Circle<'dl> {
+    fn new(
+        draw_list: &'dl WindowDrawList<'dl>,
+        center: ImVec2,
+        radius: f32,
+        color: ImColor,
+    ) -> Self {
+        Circle {
+            draw_list: draw_list,
+            center: center,
+            radius: radius,
+            color: color,
+            num_segments: 12,
+            thickness: 1.0,
+            filled: false,
+        }
+    }
+    /// Sets the number of segments used to approximate the circle (default: 12).
+    #[inline]
+    pub fn num_segments(mut self, num_segments: u32) -> Self {
+        self.num_segments = num_segments;
+        self
+    }
+    /// Sets the thickness of the outline (default: 1.0).
+    ///
+    /// ImGui 1.52 always draws circle outlines with a thickness of 1.0, so thicker outlines
+    /// are drawn as a closed polyline.
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Enables/disables filling the circle instead of drawing its outline (default: false).
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+        self
+    }
+    /// Draws the circle.
+    pub fn build(self) {
+        let draw_list = self.draw_list.raw();
+        let col = self.color.to_u32();
+        let num_segments = self.num_segments as c_int;
+        unsafe {
+            if self.filled {
+                sys::ImDrawList_AddCircleFilled(
+                    draw_list,
+                    self.center,
+                    self.radius,
+                    col,
+                    num_segments,
+                );
+            } else if self.thickness == 1.0 {
+                sys::ImDrawList_AddCircle(draw_list, self.center, self.radius, col, num_segments);
+            } else {
+                // Same path as AddCircle, which has no thickness parameter in this version
+                let a_max = PI * 2.0 * (num_segments as f32 - 1.0) / num_segments as f32;
+                sys::ImDrawList_PathArcTo(
+                    draw_list,
+                    self.center,
+                    self.radius - 0.5,
+                    0.0,
+                    a_max,
+                    num_segments,
+                );
+                sys::ImDrawList_PathStroke(draw_list, col, true, self.thickness);
+            }
+        }
+    }
+}
+
+/// Builder for a text.
+#[must_use]
+pub struct DrawText<'dl, 'p> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    pos: ImVec2,
+    color: ImColor,
+    text: &'p str,
+    font: Option<(FontId, f32)>,
+    wrap_width: f32,
+}
+
+impl<'dl, 'p> DrawText<'dl, 'p> {
+    fn new(
+        draw_list: &'dl WindowDrawList<'dl>,
+        pos: ImVec2,
+        color: ImColor,
+        text: &'p str,
+    ) -> Self {
+        DrawText {
+            draw_list: draw_list,
+            pos: pos,
+            color: color,
+            text: text,
+            font: None,
+            wrap_width: 0.0,
+        }
+    }
+    /// Sets the font and its size in pixels (default: the current font at its current size).
+    #[inline]
+    pub fn font(mut self, font: FontId, size: f32) -> Self {
+        self.font = Some((font, size));
+        self
+    }
+    /// Sets the width at which the text wraps to a new line (default: 0.0, no wrapping).
+    #[inline]
+    pub fn wrap_width(mut self, wrap_width: f32) -> Self {
+        self.wrap_width = wrap_width;
+        self
+    }
+    /// Draws the text.
+    pub fn build(self) {
+        let begin = self.text.as_ptr() as *const c_char;
+        unsafe {
+            let end = begin.offset(self.text.len() as isize);
+            let (font, size) = match self.font {
+                Some((font, size)) => (font.font_ptr() as *const sys::ImFont, size),
+                None => (sys::igGetFont() as *const sys::ImFont, sys::igGetFontSize()),
+            };
+            sys::ImDrawList_AddTextExt(
+                self.draw_list.raw(),
+                font,
+                size,
+                self.pos,
+                self.color.to_u32(),
+                begin,
+                end,
+                self.wrap_width,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+/// Builder for an image.
+#[must_use]
+pub struct DrawImage<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    texture_id: TextureId,
+    p_min: ImVec2,
+    p_max: ImVec2,
+    uv_min: ImVec2,
+    uv_max: ImVec2,
+    color: ImColor,
+}
+
+impl<'dl> DrawImage<'dl> {
+    fn new(
+        draw_list: &'dl WindowDrawList<'dl>,
+        texture_id: TextureId,
+        p_min: ImVec2,
+        p_max: ImVec2,
+    ) -> Self {
+        DrawImage {
+            draw_list: draw_list,
+            texture_id: texture_id,
+            p_min: p_min,
+            p_max: p_max,
+            uv_min: ImVec2::new(0.0, 0.0),
+            uv_max: ImVec2::new(1.0, 1.0),
+            color: ImColor::from_rgba_u8(255, 255, 255, 255),
+        }
+    }
+    /// Sets the texture coordinates of the upper left corner (default: (0.0, 0.0)).
+    #[inline]
+    pub fn uv_min<T: Into<ImVec2>>(mut self, uv_min: T) -> Self {
+        self.uv_min = uv_min.into();
+        self
+    }
+    /// Sets the texture coordinates of the lower right corner (default: (1.0, 1.0)).
+    #[inline]
+    pub fn uv_max<T: Into<ImVec2>>(mut self, uv_max: T) -> Self {
+        self.uv_max = uv_max.into();
+        self
+    }
+    /// Sets the color multiplied with the texture (default: white, no tint).
+    #[inline]
+    pub fn color<C: Into<ImColor>>(mut self, color: C) -> Self {
+        self.color = color.into();
+        self
+    }
+    /// Draws the image.
+    pub fn build(self) {
+        unsafe {
+            sys::ImDrawList_AddImage(
+                self.draw_list.raw(),
+                self.texture_id.as_ptr(),
+                self.p_min,
+                self.p_max,
+                self.uv_min,
+                self.uv_max,
+                self.color.to_u32(),
+            );
+        }
+    }
+}
+
+/// Builder for a polyline or a convex polygon.
+#[must_use]
+pub struct Polyline<'dl, 'p> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    points: &'p [ImVec2],
+    color: ImColor,
+    closed: bool,
+    thickness: f32,
+    filled: bool,
+}
+
+impl<'dl, 'p> Polyline<'dl, 'p> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, points: &'p [ImVec2], color: ImColor) -> Self {
+        Polyline {
+            draw_list: draw_list,
+            points: points,
+            color: color,
+            closed: false,
+            thickness: 1.0,
+            filled: false,
+        }
+    }
+    /// Enables/disables connecting the last point to the first one (default: false).
+    #[inline]
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+    /// Sets the thickness of the line (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Enables/disables filling the polygon instead of drawing a line (default: false).
+    ///
+    /// Only convex polygons are filled correctly.
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+        self
+    }
+    /// Draws the polyline or polygon.
+    pub fn build(self) {
+        unsafe {
+            let style = &*sys::igGetStyle();
+            if self.filled {
+                sys::ImDrawList_AddConvexPolyFilled(
+                    self.draw_list.raw(),
+                    self.points.as_ptr(),
+                    self.points.len() as c_int,
+                    self.color.to_u32(),
+                    style.anti_aliased_shapes,
+                );
+            } else {
+                sys::ImDrawList_AddPolyLine(
+                    self.draw_list.raw(),
+                    self.points.as_ptr(),
+                    self.points.len() as c_int,
+                    self.color.to_u32(),
+                    self.closed,
+                    self.thickness,
+                    style.anti_aliased_lines,
+                );
+            }
+        }
+    }
+}
+
+/// Builder for a cubic bezier curve.
+#[must_use]
+pub struct BezierCurve<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    points: [ImVec2; 4],
+    color: ImColor,
+    thickness: f32,
+    num_segments: u32,
+}
+
+impl<'dl> BezierCurve<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, points: [ImVec2; 4], color: ImColor) -> Self {
+        BezierCurve {
+            draw_list: draw_list,
+            points: points,
+            color: color,
+            thickness: 1.0,
+            num_segments: 0,
+        }
+    }
+    /// Sets the thickness of the curve (default: 1.0).
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+    /// Sets the number of segments used to approximate the curve (default: 0, which
+    /// subdivides the curve according to the style's curve tessellation tolerance).
+    #[inline]
+    pub fn num_segments(mut self, num_segments: u32) -> Self {
+        self.num_segments = num_segments;
+        self
+    }
+    /// Draws the curve.
+    pub fn build(self) {
+        let (pos0, cp0) = (self.points[0], self.points[1]);
+        let (cp1, pos1) = (self.points[2], self.points[3]);
+        unsafe {
+            sys::ImDrawList_AddBezierCurve(
+                self.draw_list.raw(),
+                pos0,
+                cp0,
+                cp1,
+                pos1,
+                self.color.to_u32(),
+                self.thickness,
+                self.num_segments as c_int,
+            );
+        }
+    }
+}
+
+/// Builder for a path, which is drawn by filling or stroking it.
+///
+/// The lines, arcs and curves are recorded by the builder and only added to the draw list when
+/// the path is drawn, so widgets drawn in the meantime don't affect it.
+#[must_use]
+pub struct Path<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    segments: Vec<PathSegment>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum PathSegment {
+    LineTo(ImVec2),
+    LineToMergeDuplicate(ImVec2),
+    ArcTo(ImVec2, f32, f32, f32, c_int),
+    ArcToFast(ImVec2, f32, c_int, c_int),
+    BezierCurveTo(ImVec2, ImVec2, ImVec2, c_int),
+    Rect(ImVec2, ImVec2, f32, ImDrawCornerFlags),
+}
+
+impl<'dl> Path<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>) -> Self {
+        Path {
+            draw_list: draw_list,
+            segments: Vec::new(),
+        }
+    }
+    /// Adds a line from the previous point to `pos`.
+    #[inline]
+    pub fn line_to<P: Into<ImVec2>>(mut self, pos: P) -> Self {
+        self.segments.push(PathSegment::LineTo(pos.into()));
+        self
+    }
+    /// Adds a line from the previous point to `pos`, unless `pos` is the previous point.
+    #[inline]
+    pub fn line_to_merge_duplicate<P: Into<ImVec2>>(mut self, pos: P) -> Self {
+        self.segments.push(PathSegment::LineToMergeDuplicate(pos.into()));
+        self
+    }
+    /// Adds an arc of a circle from angle `a_min` to `a_max` in radians, approximated by
+    /// `num_segments` segments.
+    #[inline]
+    pub fn arc_to<P: Into<ImVec2>>(
+        mut self,
+        center: P,
+        radius: f32,
+        a_min: f32,
+        a_max: f32,
+        num_segments: u32,
+    ) -> Self {
+        self.segments.push(PathSegment::ArcTo(
+            center.into(),
+            radius,
+            a_min,
+            a_max,
+            num_segments as c_int,
+        ));
+        self
+    }
+    /// Adds an arc of a circle using precomputed angles, which are given in twelfths of a full
+    /// turn (e.g. 0 to 3 for a quarter of a circle).
+    #[inline]
+    pub fn arc_to_fast<P: Into<ImVec2>>(
+        mut self,
+        center: P,
+        radius: f32,
+        a_min_of_12: i32,
+        a_max_of_12: i32,
+    ) -> Self {
+        self.segments.push(PathSegment::ArcToFast(
+            center.into(),
+            radius,
+            a_min_of_12,
+            a_max_of_12,
+        ));
+        self
+    }
+    /// Adds a cubic bezier curve from the previous point to `p3`, with the control points `p1`
+    /// and `p2`.
+    ///
+    /// With 0 segments, the curve is subdivided according to the style's curve tessellation
+    /// tolerance.
+    #[inline]
+    pub fn bezier_curve_to<P1, P2, P3>(mut self, p1: P1, p2: P2, p3: P3, num_segments: u32) -> Self
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        P3: Into<ImVec2>,
+    {
+        self.segments.push(PathSegment::BezierCurveTo(
+            p1.into(),
+            p2.into(),
+            p3.into(),
+            num_segments as c_int,
+        ));
+        self
+    }
+    /// Adds a rectangle, with the given corners rounded by `rounding`.
+    #[inline]
+    pub fn rect<P1, P2>(
+        mut self,
+        rect_min: P1,
+        rect_max: P2,
+        rounding: f32,
+        rounding_corners: ImDrawCornerFlags,
+    ) -> Self
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+    {
+        self.segments.push(PathSegment::Rect(
+            rect_min.into(),
+            rect_max.into(),
+            rounding,
+            rounding_corners,
+        ));
+        self
+    }
+    /// Fills the path, which must be a convex shape.
+    pub fn fill<C: Into<ImColor>>(self, color: C) {
+        let draw_list = self.build();
+        unsafe { sys::ImDrawList_PathFillConvex(draw_list, color.into().to_u32()) }
+    }
+    /// Draws a line along the path, connecting the last point to the first one if `closed` is
+    /// true.
+    pub fn stroke<C: Into<ImColor>>(self, color: C, closed: bool, thickness: f32) {
+        let draw_list = self.build();
+        unsafe {
+            sys::ImDrawList_PathStroke(draw_list, color.into().to_u32(), closed, thickness);
+        }
+    }
+    /// Adds the recorded segments to the draw list's path, which ImGui clears after drawing it.
+    fn build(&self) -> *mut sys::ImDrawList {
+        let draw_list = self.draw_list.raw();
+        unsafe {
+            sys::ImDrawList_PathClear(draw_list);
+            for segment in &self.segments {
+                match *segment {
+                    PathSegment::LineTo(pos) => sys::ImDrawList_PathLineTo(draw_list, pos),
+                    PathSegment::LineToMergeDuplicate(pos) => {
+                        sys::ImDrawList_PathLineToMergeDuplicate(draw_list, pos)
+                    }
+                    PathSegment::ArcTo(center, radius, a_min, a_max, num_segments) => {
+                        sys::ImDrawList_PathArcTo(
+                            draw_list,
+                            center,
+                            radius,
+                            a_min,
+                            a_max,
+                            num_segments,
+                        )
+                    }
+                    PathSegment::ArcToFast(center, radius, a_min_of_12, a_max_of_12) => {
+                        sys::ImDrawList_PathArcToFast(
+                            draw_list,
+                            center,
+                            radius,
+                            a_min_of_12,
+                            a_max_of_12,
+                        )
+                    }
+                    PathSegment::BezierCurveTo(p1, p2, p3, num_segments) => {
+                        sys::ImDrawList_PathBezierCurveTo(draw_list, p1, p2, p3, num_segments)
+                    }
+                    PathSegment::Rect(rect_min, rect_max, rounding, rounding_corners) => {
+                        sys::ImDrawList_PathRect(
+                            draw_list,
+                            rect_min,
+                            rect_max,
+                            rounding,
+                            rounding_corners.bits(),
+                        )
+                    }
+                }
+            }
+        }
+        draw_list
+    }
+}
+
+/// Writer for the vertices and indices of a mesh, which form triangles.
+///
+/// Indices are relative to the first vertex of the mesh. The mesh is added to the draw list
+/// when the writer is dropped, with the texture, clip rectangle and channel current at that
+/// point, so widgets can be drawn while it is written. Trailing indices which don't form a
+/// whole triangle are dropped.
+pub struct MeshWriter<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    vtx: Vec<ImDrawVert>,
+    idx: Vec<ImDrawIdx>,
+    vtx_count: u32,
+    idx_count: u32,
+    // Number of vertices referenced by the written indices
+    vtx_referenced: u32,
+}
+
+impl<'dl> MeshWriter<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, idx_count: u32, vtx_count: u32) -> Self {
+        check_vertex_space(draw_list.raw(), vtx_count);
+        assert!(
+            idx_count <= c_int::max_value() as u32 && vtx_count <= c_int::max_value() as u32,
+            "Too many indices or vertices"
+        );
+        MeshWriter {
+            draw_list: draw_list,
+            vtx: Vec::with_capacity(vtx_count as usize),
+            idx: Vec::with_capacity(idx_count as usize),
+            vtx_count: vtx_count,
+            idx_count: idx_count,
+            vtx_referenced: 0,
+        }
+    }
+    /// Returns the number of vertices which can still be written.
+    pub fn vertices_left(&self) -> u32 { self.vtx_count - self.vtx.len() as u32 }
+    /// Returns the number of indices which can still be written.
+    pub fn indices_left(&self) -> u32 { self.idx_count - self.idx.len() as u32 }
+    /// Writes a vertex.
+    ///
+    /// # Panics
+    /// Panics if all reserved vertices have been written.
+    pub fn write_vertex<P, T, C>(&mut self, pos: P, uv: T, color: C)
+    where
+        P: Into<ImVec2>,
+        T: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        self.reserve_written(0, 1);
+        self.vtx.push(ImDrawVert {
+            pos: pos.into(),
+            uv: uv.into(),
+            col: color.into().to_u32(),
+        });
+    }
+    /// Writes vertices.
+    ///
+    /// # Panics
+    /// Panics if there are more vertices than reserved ones left.
+    pub fn write_vertices(&mut self, vertices: &[ImDrawVert]) {
+        self.reserve_written(0, vertices.len());
+        self.vtx.extend_from_slice(vertices);
+    }
+    /// Writes an index, relative to the first vertex of the mesh.
+    ///
+    /// # Panics
+    /// Panics if all reserved indices have been written, or if the index is out of the range
+    /// of reserved vertices.
+    pub fn write_index(&mut self, idx: ImDrawIdx) {
+        self.check_index(idx);
+        self.reserve_written(1, 0);
+        self.idx.push(idx);
+    }
+    /// Writes indices, relative to the first vertex of the mesh.
+    ///
+    /// # Panics
+    /// Panics if there are more indices than reserved ones left, or if an index is out of the
+    /// range of reserved vertices.
+    pub fn write_indices(&mut self, indices: &[ImDrawIdx]) {
+        for &idx in indices {
+            self.check_index(idx);
+        }
+        self.reserve_written(indices.len(), 0);
+        self.idx.extend_from_slice(indices);
+    }
+    /// Writes an axis-aligned rectangle with the upper left corner `a` and the lower right
+    /// corner `b`, using 4 vertices and 6 indices.
+    ///
+    /// # Panics
+    /// Panics if fewer reserved vertices or indices are left.
+    pub fn write_rect_uv<P1, P2, T1, T2, C>(&mut self, a: P1, b: P2, uv_a: T1, uv_b: T2, color: C)
+    where
+        P1: Into<ImVec2>,
+        P2: Into<ImVec2>,
+        T1: Into<ImVec2>,
+        T2: Into<ImVec2>,
+        C: Into<ImColor>,
+    {
+        let (a, c, uv_a, uv_c) = (a.into(), b.into(), uv_a.into(), uv_b.into());
+        // Same corners as ImDrawList::PrimRectUV
+        self.write_quad_uv(
+            [a, ImVec2::new(c.x, a.y), c, ImVec2::new(a.x, c.y)],
+            [uv_a, ImVec2::new(uv_c.x, uv_a.y), uv_c, ImVec2::new(uv_a.x, uv_c.y)],
+            color,
+        );
+    }
+    /// Writes a quad with the corners `a`, `b`, `c` and `d` in order, using 4 vertices and 6
+    /// indices.
+    ///
+    /// # Panics
+    /// Panics if fewer reserved vertices or indices are left.
+    pub fn write_quad_uv<C>(&mut self, corners: [ImVec2; 4], uvs: [ImVec2; 4], color: C)
+    where
+        C: Into<ImColor>,
+    {
+        self.reserve_written(6, 4);
+        let col = color.into().to_u32();
+        let base = self.vtx.len() as ImDrawIdx;
+        for i in 0..4 {
+            self.vtx.push(ImDrawVert {
+                pos: corners[i],
+                uv: uvs[i],
+                col: col,
+            });
+        }
+        self.idx.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        self.vtx_referenced = cmp::max(self.vtx_referenced, self.vtx.len() as u32);
+    }
+    fn reserve_written(&self, idx_count: usize, vtx_count: usize) {
+        assert!(
+            idx_count <= self.indices_left() as usize,
+            "Writing {} indices, but only {} reserved ones are left",
+            idx_count,
+            self.indices_left()
+        );
+        assert!(
+            vtx_count <= self.vertices_left() as usize,
+            "Writing {} vertices, but only {} reserved ones are left",
+            vtx_count,
+            self.vertices_left()
+        );
+    }
+    fn check_index(&mut self, idx: ImDrawIdx) {
+        assert!(
+            (idx as u32) < self.vtx_count,
+            "Index {} is out of range, the mesh has {} vertices",
+            idx,
+            self.vtx_count
+        );
+        self.vtx_referenced = cmp::max(self.vtx_referenced, idx as u32 + 1);
+    }
+}
+
+impl<'dl> Drop for MeshWriter<'dl> {
+    fn drop(&mut self) {
+        let triangles_len = self.idx.len() - self.idx.len() % 3;
+        self.idx.truncate(triangles_len);
+        // Nothing is added while unwinding, where failing the check below would abort
+        if self.idx.is_empty() || thread::panicking() {
+            return;
+        }
+        // Vertices referenced by written indices are added, so that no index is out of range
+        while (self.vtx.len() as u32) < self.vtx_referenced {
+            self.vtx.push(ImDrawVert::default());
+        }
+        let draw_list = self.draw_list.raw();
+        // Widgets may have added vertices since the writer was created
+        check_vertex_space(draw_list, self.vtx.len() as u32);
+        unsafe {
+            let vtx_base = (*draw_list).vtx_buffer.size as u32;
+            sys::ImDrawList_PrimReserve(
+                draw_list,
+                self.idx.len() as c_int,
+                self.vtx.len() as c_int,
+            );
+            for vtx in &self.vtx {
+                sys::ImDrawList_PrimWriteVtx(draw_list, vtx.pos, vtx.uv, vtx.col);
+            }
+            for &idx in &self.idx {
+                sys::ImDrawList_PrimWriteIdx(draw_list, (vtx_base + idx as u32) as ImDrawIdx);
+            }
+        }
+    }
+}
+
+fn check_vertex_space(draw_list: *mut sys::ImDrawList, vtx_count: u32) {
+    let vtx_base = unsafe { (*draw_list).vtx_buffer.size as u32 };
+    let max_vertices = ImDrawIdx::max_value() as u64 + 1;
+    assert!(
+        vtx_base as u64 + vtx_count as u64 <= max_vertices,
+        "The draw list can't hold {} more vertices, it has {} of at most {}",
+        vtx_count,
+        vtx_base,
+        max_vertices
+    );
+}
+
+/// Guard for a draw list split into channels, which are merged when it is dropped.
+///
+/// The guard dereferences to the draw list, so shapes can be drawn through it.
+pub struct ChannelsSplit<'dl> {
+    draw_list: &'dl WindowDrawList<'dl>,
+    channels_count: u32,
+}
+
+impl<'dl> ChannelsSplit<'dl> {
+    fn new(draw_list: &'dl WindowDrawList<'dl>, channels_count: u32) -> Self {
+        assert!(channels_count > 0, "A draw list needs at least one channel");
+        assert!(
+            unsafe { (*draw_list.raw()).channels_count } == 1,
+            "The draw list is already split into channels, e.g. by columns"
+        );
+        unsafe { sys::ImDrawList_ChannelsSplit(draw_list.raw(), channels_count as c_int) }
+        ChannelsSplit {
+            draw_list: draw_list,
+            channels_count: channels_count,
+        }
+    }
+    /// Returns the number of channels.
+    pub fn channels_count(&self) -> u32 { self.channels_count }
+    /// Sets the channel which following shapes are drawn into. Channel 0 is current after
+    /// splitting.
+    ///
+    /// # Panics
+    /// Panics if the channel index is out of range.
+    pub fn set_current(&self, channel_index: u32) {
+        assert!(
+            channel_index < self.channels_count,
+            "Channel index {} is out of range, the draw list has {} channels",
+            channel_index,
+            self.channels_count
+        );
+        unsafe { sys::ImDrawList_ChannelsSetCurrent(self.draw_list.raw(), channel_index as c_int) }
+    }
+}
+
+impl<'dl> Deref for ChannelsSplit<'dl> {
+    type Target = WindowDrawList<'dl>;
+    fn deref(&self) -> &WindowDrawList<'dl> { self.draw_list }
+}
+
+impl<'dl> Drop for ChannelsSplit<'dl> {
+    fn drop(&mut self) { unsafe { sys::ImDrawList_ChannelsMerge(self.draw_list.raw()) } }
+}
+
+/// A draw list which doesn't belong to a window, rendered before or after the windows.
+pub(crate) struct OwnedDrawList(*mut sys::ImDrawList);
+
+impl OwnedDrawList {
+    pub fn new() -> OwnedDrawList {
+        unsafe {
+            // ImDrawList's constructor just clears the list, which works on a zeroed list
+            let list = Box::into_raw(Box::new(mem::zeroed::<sys::ImDrawList>()));
+            sys::ImDrawList_Clear(list);
+            OwnedDrawList(list)
+        }
+    }
+    pub fn raw(&self) -> *mut sys::ImDrawList { self.0 }
+    /// Clears the list and sets it up for drawing over the whole display with the font
+    /// texture. Must be called after `igNewFrame`.
+    pub unsafe fn new_frame(&self) {
+        sys::ImDrawList_Clear(self.0);
+        sys::ImDrawList_PushTextureID(self.0, (*(*sys::igGetIO()).fonts).tex_id);
+        sys::ImDrawList_PushClipRectFullScreen(self.0);
+    }
+    /// Removes the trailing empty command like ImGui does for window draw lists, and returns
+    /// whether there is anything left to render.
+    pub unsafe fn prepare_render(&self) -> bool {
+        let cmds = &mut (*self.0).cmd_buffer;
+        if cmds.size > 0 {
+            let last = &*cmds.data.offset(cmds.size as isize - 1);
+            if last.elem_count == 0 && last.user_callback.is_none() {
+                cmds.size -= 1;
+            }
+        }
+        cmds.size > 0
+    }
+}
+
+impl Drop for OwnedDrawList {
+    fn drop(&mut self) {
+        unsafe {
+            sys::ImDrawList_ClearFreeMemory(self.0);
+            drop(Box::from_raw(self.0));
+        }
+    }
+}
+
+/// Information passed by the renderer to a draw callback.
+pub struct DrawCallbackContext<'a> {
+    /// Clip rectangle of the callback as (left, top, right, bottom), in display coordinates.
+    pub clip_rect: ImVec4,
+    /// Size of the display.
+    pub display_size: ImVec2,
+    /// Scale of the framebuffer relative to the display size.
+    pub framebuffer_scale: ImVec2,
+    /// Renderer-specific render target, which can be downcast to the renderer's type. The gfx
+    /// renderer passes its `gfx::Encoder<R, C>`. The glium renderer passes the surface it
+    /// renders to (e.g. `glium::Frame`) in `render_with_callback_target` only.
+    pub target: Option<&'a mut Any>,
+}
+
+impl<'a> DrawCallbackContext<'a> {
+    /// Returns the clip rectangle as (left, top, right, bottom) in framebuffer pixels.
+    pub fn framebuffer_clip_rect(&self) -> ImVec4 {
+        ImVec4::new(
+            self.clip_rect.x * self.framebuffer_scale.x,
+            self.clip_rect.y * self.framebuffer_scale.y,
+            self.clip_rect.z * self.framebuffer_scale.x,
+            self.clip_rect.w * self.framebuffer_scale.y,
+        )
+    }
+}
+
+/// A closure added with `WindowDrawList::add_callback`, which renderers take with
+/// `Ui::take_draw_callback` and run in draw order.
+pub struct DrawCallback(Box<for<'a, 'b> FnMut(&'a mut DrawCallbackContext<'b>)>);
+
+impl DrawCallback {
+    /// Runs the closure.
+    pub fn run(&mut self, ctx: &mut DrawCallbackContext) { (self.0)(ctx) }
+}
+
+impl fmt::Debug for DrawCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("DrawCallback") }
+}
+
+type RawDrawCallback = extern "C" fn(*const sys::ImDrawList, *const ImDrawCmd);
+
+// Identifies the commands of our callbacks, ImGui itself never calls it
+extern "C" fn draw_callback_marker(_: *const sys::ImDrawList, _: *const ImDrawCmd) {}
+
+pub(crate) fn take_draw_callback(imgui: &ImGui, cmd: &ImDrawCmd) -> Option<DrawCallback> {
+    let marker = draw_callback_marker as RawDrawCallback as usize;
+    match cmd.user_callback {
+        Some(callback) if callback as usize == marker => imgui
+            .draw_callbacks
+            .borrow_mut()
+            .get_mut(cmd.user_callback_data as usize)
+            .and_then(|callback| callback.take()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{DrawList, ImString};
+
+    #[test]
+    fn test_mesh_with_widget_drawn_in_between() {
+        let mut imgui = ImGui::init();
+        imgui.prepare_texture(|_| ());
+        let ui = imgui.frame((200, 200), (200, 200), 0.1);
+        ui.window(&ImString::new("Mesh")).build(|| {
+            let draw_list = ui.get_window_draw_list();
+            let uv = draw_list.white_pixel_uv();
+            {
+                let mut mesh = draw_list.mesh(6, 3);
+                mesh.write_vertex((0.0, 0.0), uv, [1.0, 0.0, 0.0]);
+                mesh.write_indices(&[0, 1, 2]);
+                // The button adds vertices and indices to the same draw list
+                ui.button(&ImString::new("Button"), (0.0, 0.0));
+                mesh.write_vertex((10.0, 0.0), uv, [1.0, 0.0, 0.0]);
+                mesh.write_vertex((0.0, 10.0), uv, [1.0, 0.0, 0.0]);
+                mesh.write_indices(&[2, 1]);
+            }
+            let list = unsafe { DrawList::from_raw(draw_list.raw()) };
+            let vtx_len = list.vtx_buffer.len();
+            let idx_len = list.idx_buffer.len();
+            // The mesh comes after the button, and its incomplete triangle is dropped
+            let base = (vtx_len - 3) as ImDrawIdx;
+            assert_eq!(&list.idx_buffer[idx_len - 3..], &[base, base + 1, base + 2]);
+            assert_eq!(list.vtx_buffer[vtx_len - 3].pos, ImVec2::new(0.0, 0.0));
+            assert_eq!(list.vtx_buffer[vtx_len - 2].pos, ImVec2::new(10.0, 0.0));
+            let elem_count: u32 = list.cmd_buffer.iter().map(|cmd| cmd.elem_count).sum();
+            assert_eq!(elem_count as usize, idx_len);
+        });
+    }
+}
@@ -0,0 +1,580 @@
+#![warn(missing_docs)]
+use sys;
+use std::fmt;
+use std::fs::File;
+use std::f32;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::path::Path;
+use std::ptr;
+
+use super::{GlyphRanges, ImGui, ImVec2, Ui};
+
+/// Handle to a font added to the font atlas.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+impl FontId {
+    /// Returns the index of the font in the atlas.
+    pub fn index(self) -> usize { self.0 }
+    /// Returns the font in the current atlas.
+    ///
+    /// # Panics
+    /// Panics if the atlas doesn't contain the font, or if the font hasn't been built into the
+    /// font texture yet.
+    pub(crate) fn font_ptr(self) -> *mut sys::ImFont {
+        unsafe {
+            let atlas = (*sys::igGetIO()).fonts;
+            assert!(
+                self.0 < sys::ImFontAtlas_Fonts_size(atlas) as usize,
+                "Font {} is not in the font atlas",
+                self.0
+            );
+            let font = sys::ImFontAtlas_Fonts_index(atlas, self.0 as c_int);
+            assert!(
+                sys::ImFont_IsLoaded(font),
+                "Font {} has not been built, reload the font texture first",
+                self.0
+            );
+            font
+        }
+    }
+    /// Returns the id of a font of the current atlas.
+    pub(crate) fn from_ptr(font: *mut sys::ImFont) -> FontId {
+        unsafe {
+            let atlas = (*sys::igGetIO()).fonts;
+            let len = sys::ImFontAtlas_Fonts_size(atlas);
+            (0..len)
+                .position(|i| sys::ImFontAtlas_Fonts_index(atlas, i) == font)
+                .map(FontId)
+                .expect("Font is not in the font atlas")
+        }
+    }
+}
+
+/// Metrics of a glyph, in pixels at the size the font was built with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FontGlyph {
+    /// Horizontal distance to the next glyph.
+    pub advance_x: f32,
+    /// Upper left corner of the glyph quad, relative to the pen position at the top of the line.
+    pub min: ImVec2,
+    /// Lower right corner of the glyph quad, relative to the pen position at the top of the
+    /// line.
+    pub max: ImVec2,
+    /// Texture coordinates of the upper left corner in the font atlas.
+    pub uv_min: ImVec2,
+    /// Texture coordinates of the lower right corner in the font atlas.
+    pub uv_max: ImVec2,
+}
+
+/// A font of the font atlas, for querying metrics and laying out text in custom widgets.
+///
+/// Sizes are in pixels. Metrics are given at the size the font was built with, text layout
+/// queries take the size to lay out the text at, e.g. `Ui::current_font_size`.
+///
+/// # Example
+/// ```rust,no_run
+/// # use imgui::*;
+/// # let mut imgui = ImGui::init();
+/// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+/// let font = ui.font(ui.current_font());
+/// let size = ui.current_font_size();
+/// let text = "Hello world";
+/// // Caret position after the first word
+/// let caret_x = font.calc_text_size(size, &text[..5], 0.0).x;
+/// let baseline_y = font.ascent() * size / font.size();
+/// ```
+pub struct Font<'ui> {
+    id: FontId,
+    raw: *const sys::ImFont,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui> Font<'ui> {
+    pub(crate) fn new(_: &Ui<'ui>, id: FontId) -> Font<'ui> {
+        Font {
+            id: id,
+            raw: id.font_ptr(),
+            _phantom: PhantomData,
+        }
+    }
+    /// Returns the id of the font.
+    pub fn id(&self) -> FontId { self.id }
+    /// Returns the size the font was built with.
+    pub fn size(&self) -> f32 { unsafe { sys::ImFont_GetFontSize(self.raw) } }
+    /// Returns the scale applied to the font when rendering (default: 1.0).
+    pub fn scale(&self) -> f32 { unsafe { sys::ImFont_GetScale(self.raw) } }
+    /// Returns the distance from the top of the line to the baseline.
+    pub fn ascent(&self) -> f32 { unsafe { sys::ImFont_GetAscent(self.raw) } }
+    /// Returns the distance from the baseline to the bottom of the line, which is negative.
+    pub fn descent(&self) -> f32 { unsafe { sys::ImFont_GetDescent(self.raw) } }
+    /// Returns the character displayed in place of characters missing from the font.
+    pub fn fallback_char(&self) -> char {
+        let c = unsafe { sys::ImFont_GetFallbackChar(self.raw) };
+        char::from_u32(c as u32).unwrap_or('?')
+    }
+    /// Returns the glyph of a character, or `None` if the font doesn't contain it.
+    pub fn glyph(&self, c: char) -> Option<FontGlyph> {
+        let wc = match to_wchar(c) {
+            Some(wc) => wc,
+            None => return None,
+        };
+        let glyph = unsafe { sys::ImFont_FindGlyph(self.raw, wc) };
+        // Missing characters are mapped to the fallback glyph
+        if glyph.is_null() || unsafe { (*glyph).codepoint } != wc {
+            return None;
+        }
+        let glyph = unsafe { &*glyph };
+        Some(FontGlyph {
+            advance_x: glyph.advance_x,
+            min: ImVec2::new(glyph.x0, glyph.y0),
+            max: ImVec2::new(glyph.x1, glyph.y1),
+            uv_min: ImVec2::new(glyph.u0, glyph.v0),
+            uv_max: ImVec2::new(glyph.u1, glyph.v1),
+        })
+    }
+    /// Returns true if the font contains the character.
+    pub fn has_glyph(&self, c: char) -> bool { self.glyph(c).is_some() }
+    /// Returns the horizontal advance of a character, which is the advance of the fallback
+    /// glyph for missing characters.
+    pub fn char_advance(&self, c: char) -> f32 {
+        match to_wchar(c) {
+            Some(wc) => unsafe { sys::ImFont_GetCharAdvance(self.raw, wc) },
+            None => unsafe { sys::ImFont_GetFallbackAdvanceX(self.raw) },
+        }
+    }
+    /// Calculates the size of the text rendered at the given size.
+    ///
+    /// The text is wrapped at `wrap_width` if it is positive.
+    pub fn calc_text_size(&self, size: f32, text: &str, wrap_width: f32) -> ImVec2 {
+        self.calc_text_size_clipped(size, f32::MAX, text, wrap_width).0
+    }
+    /// Calculates the size of the longest prefix of the text which fits into `max_width`.
+    ///
+    /// Returns the size and the length in bytes of the prefix.
+    pub fn calc_text_size_clipped(
+        &self,
+        size: f32,
+        max_width: f32,
+        text: &str,
+        wrap_width: f32,
+    ) -> (ImVec2, usize) {
+        let mut out = ImVec2::zero();
+        let begin = text.as_ptr() as *const c_char;
+        let mut remaining: *const c_char = ptr::null();
+        unsafe {
+            sys::ImFont_CalcTextSizeA(
+                self.raw,
+                &mut out,
+                size,
+                max_width,
+                wrap_width,
+                begin,
+                begin.offset(text.len() as isize),
+                &mut remaining,
+            );
+        }
+        (out, remaining as usize - begin as usize)
+    }
+    /// Returns the byte offset at which the text wraps to the next line when rendered with the
+    /// given scale relative to the font size.
+    pub fn word_wrap_position(&self, scale: f32, text: &str, wrap_width: f32) -> usize {
+        let begin = text.as_ptr() as *const c_char;
+        let position = unsafe {
+            sys::ImFont_CalcWordWrapPositionA(
+                self.raw,
+                scale,
+                begin,
+                begin.offset(text.len() as isize),
+                wrap_width,
+            )
+        };
+        position as usize - begin as usize
+    }
+}
+
+fn to_wchar(c: char) -> Option<sys::ImWchar> {
+    let c = c as u32;
+    if c <= 0xFFFF {
+        Some(c as sys::ImWchar)
+    } else {
+        None
+    }
+}
+
+/// A character of an icon font, e.g. FontAwesome.
+///
+/// Icons are displayed inline with text once the icon font has been merged into the current
+/// font (see `FontConfig::merge_mode`).
+///
+/// # Example
+/// ```rust,no_run
+/// # use imgui::*;
+/// const ICON_SAVE: Icon = Icon('\u{f0c7}');
+///
+/// let mut imgui = ImGui::init();
+/// imgui.fonts_mut().add_font_default(&FontConfig::new());
+/// imgui.fonts_mut()
+///     .add_font_from_file(
+///         "fontawesome-webfont.ttf",
+///         &FontConfig::new()
+///             .merge_mode(true)
+///             .glyph_min_advance_x(13.0)
+///             .glyph_ranges(GlyphRanges::empty().add_icon(ICON_SAVE)),
+///     )
+///     .expect("Failed to load font");
+/// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+/// ui.button(im_str!("{} Save", ICON_SAVE), (0.0, 0.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Icon(pub char);
+
+impl Icon {
+    /// Returns the character code of the icon.
+    pub fn char(self) -> char { self.0 }
+}
+
+impl From<Icon> for char {
+    fn from(icon: Icon) -> char { icon.0 }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+/// Configuration of a font added to the font atlas.
+#[derive(Clone, Debug)]
+pub struct FontConfig {
+    size_pixels: f32,
+    // Options left unset depend on the font, see `to_sys`
+    oversample_h: Option<u32>,
+    oversample_v: u32,
+    pixel_snap_h: Option<bool>,
+    glyph_extra_spacing: ImVec2,
+    glyph_offset: ImVec2,
+    merge_mode: bool,
+    rasterizer_multiply: f32,
+    glyph_ranges: Option<GlyphRanges>,
+    glyph_min_advance_x: f32,
+}
+
+impl Default for FontConfig {
+    fn default() -> FontConfig {
+        FontConfig {
+            size_pixels: 13.0,
+            oversample_h: None,
+            oversample_v: 1,
+            pixel_snap_h: None,
+            glyph_extra_spacing: ImVec2::zero(),
+            glyph_offset: ImVec2::zero(),
+            merge_mode: false,
+            rasterizer_multiply: 1.0,
+            glyph_ranges: None,
+            glyph_min_advance_x: 0.0,
+        }
+    }
+}
+
+impl FontConfig {
+    /// Constructs a new font configuration with the default settings (13 pixel size).
+    pub fn new() -> FontConfig { FontConfig::default() }
+    /// Sets the font size in pixels, which is multiplied by `ImGui::dpi_scale`.
+    #[inline]
+    pub fn size_pixels(mut self, size_pixels: f32) -> FontConfig {
+        self.size_pixels = size_pixels;
+        self
+    }
+    /// Sets how many times glyphs are rasterized horizontally at a higher resolution
+    /// (default: 3, or 1 for the default font).
+    ///
+    /// Oversampling improves the quality of glyphs at sub-pixel positions at the cost of a
+    /// bigger texture.
+    #[inline]
+    pub fn oversample_h(mut self, oversample_h: u32) -> FontConfig {
+        self.oversample_h = Some(oversample_h);
+        self
+    }
+    /// Sets how many times glyphs are rasterized vertically at a higher resolution (default: 1).
+    #[inline]
+    pub fn oversample_v(mut self, oversample_v: u32) -> FontConfig {
+        self.oversample_v = oversample_v;
+        self
+    }
+    /// Enables/disables aligning every glyph to the pixel grid horizontally (default: false, or
+    /// true for the default font).
+    ///
+    /// This is useful when rendering a pixel font at its native size.
+    #[inline]
+    pub fn pixel_snap_h(mut self, pixel_snap_h: bool) -> FontConfig {
+        self.pixel_snap_h = Some(pixel_snap_h);
+        self
+    }
+    /// Sets extra spacing between glyphs in pixels.
+    #[inline]
+    pub fn glyph_extra_spacing<S: Into<ImVec2>>(mut self, spacing: S) -> FontConfig {
+        self.glyph_extra_spacing = spacing.into();
+        self
+    }
+    /// Sets the offset of every glyph in pixels.
+    #[inline]
+    pub fn glyph_offset<O: Into<ImVec2>>(mut self, offset: O) -> FontConfig {
+        self.glyph_offset = offset.into();
+        self
+    }
+    /// Enables/disables merging the glyphs into the previously added font instead of adding a
+    /// new font (default: false).
+    #[inline]
+    pub fn merge_mode(mut self, merge_mode: bool) -> FontConfig {
+        self.merge_mode = merge_mode;
+        self
+    }
+    /// Sets the multiplier applied to the brightness of the rasterized glyphs (default: 1.0).
+    #[inline]
+    pub fn rasterizer_multiply(mut self, rasterizer_multiply: f32) -> FontConfig {
+        self.rasterizer_multiply = rasterizer_multiply;
+        self
+    }
+    /// Sets the characters to bake into the atlas (default: `GlyphRanges::default()`).
+    ///
+    /// The embedded default font only contains the default ranges, so this is ignored by
+    /// `FontAtlas::add_font_default`.
+    #[inline]
+    pub fn glyph_ranges(mut self, glyph_ranges: GlyphRanges) -> FontConfig {
+        self.glyph_ranges = Some(glyph_ranges);
+        self
+    }
+    /// Sets the minimum horizontal advance of the glyphs in pixels (default: 0.0).
+    ///
+    /// Narrower glyphs are centered, which is mostly useful to make the glyphs of a merged icon
+    /// font monospaced so that they line up in buttons and menus. ImGui 1.52 doesn't support
+    /// this natively, so the glyphs are adjusted after the atlas has been built.
+    ///
+    /// The adjustment applies to every glyph of the font in the configured glyph ranges. A
+    /// merged font shares the font it is merged into, so it needs explicit glyph ranges:
+    /// adding it without them panics.
+    #[inline]
+    pub fn glyph_min_advance_x(mut self, glyph_min_advance_x: f32) -> FontConfig {
+        self.glyph_min_advance_x = glyph_min_advance_x;
+        self
+    }
+    fn to_sys(&self, dpi_scale: f32, default_font: bool) -> sys::ImFontConfig {
+        let mut config = sys::ImFontConfig::default();
+        self.apply_dpi_scale(&mut config, dpi_scale);
+        let (oversample_h, pixel_snap_h) = if default_font { (1, true) } else { (3, false) };
+        config.oversample_h = self.oversample_h.unwrap_or(oversample_h) as c_int;
+        config.oversample_v = self.oversample_v as c_int;
+        config.pixel_snap_h = self.pixel_snap_h.unwrap_or(pixel_snap_h);
+        config.merge_mode = self.merge_mode;
+        config.rasterizer_multiply = self.rasterizer_multiply;
+        config
+    }
+    fn apply_dpi_scale(&self, config: &mut sys::ImFontConfig, dpi_scale: f32) {
+        config.size_pixels = self.size_pixels * dpi_scale;
+        config.glyph_extra_spacing = ImVec2::new(
+            self.glyph_extra_spacing.x * dpi_scale,
+            self.glyph_extra_spacing.y * dpi_scale,
+        );
+        config.glyph_offset = ImVec2::new(
+            self.glyph_offset.x * dpi_scale,
+            self.glyph_offset.y * dpi_scale,
+        );
+    }
+}
+
+/// The font atlas, which bakes all added fonts into a single texture.
+///
+/// Every change marks the atlas as dirty: it is rebuilt the next time the texture is prepared,
+/// and the renderer has to reload the font texture.
+pub struct FontAtlas<'a> {
+    imgui: &'a mut ImGui,
+}
+
+impl<'a> FontAtlas<'a> {
+    pub(crate) fn new(imgui: &'a mut ImGui) -> FontAtlas<'a> { FontAtlas { imgui: imgui } }
+    fn raw(&self) -> *mut sys::ImFontAtlas { self.imgui.io().fonts }
+    /// Returns the number of fonts in the atlas.
+    pub fn len(&self) -> usize { unsafe { sys::ImFontAtlas_Fonts_size(self.raw()) as usize } }
+    /// Returns true if no fonts have been added to the atlas.
+    ///
+    /// The default font is added automatically when the texture is prepared if the atlas is
+    /// empty.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    /// Adds the embedded default font (ProggyClean), using the size and settings of the given
+    /// configuration.
+    ///
+    /// Unless they are set in the configuration, oversampling and pixel snapping use ImGui's
+    /// settings for this pixel font, which keep it sharp.
+    pub fn add_font_default(&mut self, config: &FontConfig) -> FontId {
+        self.check_merge_mode(config);
+        unsafe {
+            sys::ImFontAtlas_AddFontDefault(
+                self.raw(),
+                &config.to_sys(self.imgui.dpi_scale, true),
+            );
+        }
+        self.imgui.font_configs.push(config.clone());
+        self.mark_dirty();
+        let font = self.last_font_id();
+        self.add_glyph_min_advance(font, config);
+        font
+    }
+    /// Adds a TTF/OTF font read from a file.
+    pub fn add_font_from_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        config: &FontConfig,
+    ) -> io::Result<FontId> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        Ok(self.add_font_from_bytes(&data, config))
+    }
+    /// Adds a TTF/OTF font from memory. The data is copied, so it doesn't need to outlive the
+    /// atlas.
+    pub fn add_font_from_bytes(&mut self, data: &[u8], config: &FontConfig) -> FontId {
+        assert!(config.size_pixels > 0.0, "Font size must be positive");
+        assert!(!data.is_empty(), "Font data must not be empty");
+        self.check_merge_mode(config);
+        let mut sys_config = config.to_sys(self.imgui.dpi_scale, false);
+        if let Some(ref glyph_ranges) = config.glyph_ranges {
+            // The atlas keeps a pointer to the ranges until it is cleared
+            let raw = glyph_ranges.to_raw();
+            sys_config.glyph_ranges = raw.as_ptr();
+            self.imgui.font_glyph_ranges.push(raw);
+        }
+        unsafe {
+            // The atlas takes ownership of the data and frees it with ImGui's allocator
+            let font_data = sys::igMemAlloc(data.len());
+            ptr::copy_nonoverlapping(data.as_ptr(), font_data as *mut u8, data.len());
+            sys::ImFontAtlas_AddFontFromMemoryTTF(
+                self.raw(),
+                font_data,
+                data.len() as c_int,
+                sys_config.size_pixels,
+                &sys_config,
+                ptr::null(),
+            );
+        }
+        self.imgui.font_configs.push(config.clone());
+        self.mark_dirty();
+        let font = self.last_font_id();
+        self.add_glyph_min_advance(font, config);
+        font
+    }
+    /// Removes all fonts, e.g. to add them again with a different size.
+    ///
+    /// Previously returned font ids must not be used anymore, and the font texture has to be
+    /// reloaded before the next frame. If no font is added afterwards, the default font is
+    /// added when the texture is prepared.
+    pub fn clear(&mut self) {
+        unsafe {
+            sys::ImFontAtlas_Clear(self.raw());
+        }
+        self.imgui.font_glyph_ranges.clear();
+        self.imgui.font_configs.clear();
+        self.imgui.glyph_min_advances.clear();
+        self.mark_dirty();
+    }
+    /// Rescales the fonts added through this API to the DPI scale of the context. The atlas
+    /// keeps the font data, so the fonts are rebuilt in place and their ids stay valid.
+    pub(crate) fn apply_dpi_scale(&mut self) {
+        let dpi_scale = self.imgui.dpi_scale;
+        unsafe {
+            let config_data = &(*self.raw()).config_data;
+            assert_eq!(
+                config_data.size as usize,
+                self.imgui.font_configs.len(),
+                "Fonts added to the atlas through imgui-sys can't be scaled"
+            );
+            for (i, config) in self.imgui.font_configs.iter().enumerate() {
+                config.apply_dpi_scale(&mut *config_data.data.offset(i as isize), dpi_scale);
+            }
+        }
+        self.mark_dirty();
+    }
+    fn add_glyph_min_advance(&mut self, font: FontId, config: &FontConfig) {
+        if config.glyph_min_advance_x > 0.0 {
+            let ranges = match config.glyph_ranges {
+                Some(ref glyph_ranges) => glyph_ranges.clone(),
+                None => GlyphRanges::default(),
+            };
+            self.imgui.glyph_min_advances.push(GlyphMinAdvance {
+                font: font,
+                ranges: ranges,
+                min_advance_x: config.glyph_min_advance_x,
+            });
+        }
+    }
+    fn mark_dirty(&mut self) {
+        // Drop the baked pixels so that the next prepare_texture rebuilds the atlas
+        unsafe {
+            sys::ImFontAtlas_ClearTexData(self.raw());
+        }
+        self.imgui.fonts_dirty = true;
+    }
+    fn check_merge_mode(&self, config: &FontConfig) {
+        if config.merge_mode {
+            assert!(!self.is_empty(), "Merge mode requires a previously added font");
+            // The default ranges would also widen the glyphs of the font merged into
+            assert!(
+                config.glyph_min_advance_x <= 0.0 || config.glyph_ranges.is_some(),
+                "A merged font with a minimum glyph advance requires explicit glyph ranges"
+            );
+        }
+    }
+    // Both new and merged fonts end up as the last font of the atlas
+    fn last_font_id(&self) -> FontId { FontId(self.len() - 1) }
+}
+
+/// Minimum advance of the glyphs of a font config, applied every time the atlas is built.
+pub(crate) struct GlyphMinAdvance {
+    font: FontId,
+    ranges: GlyphRanges,
+    min_advance_x: f32,
+}
+
+impl GlyphMinAdvance {
+    /// Widens and centers the glyphs. Applying it again to the same atlas has no effect.
+    pub(crate) unsafe fn apply(&self, atlas: *mut sys::ImFontAtlas, dpi_scale: f32) {
+        let min_advance_x = self.min_advance_x * dpi_scale;
+        let font = sys::ImFontAtlas_Fonts_index(atlas, self.font.0 as c_int);
+        let mut changed = false;
+        for i in 0..sys::ImFont_Glyphs_size(font) {
+            let glyph = &mut *sys::ImFont_Glyphs_index(font, i);
+            let in_ranges = self.ranges
+                .ranges()
+                .iter()
+                .any(|&(first, last)| first <= glyph.codepoint && glyph.codepoint <= last);
+            if in_ranges && glyph.advance_x < min_advance_x {
+                let offset = (min_advance_x - glyph.advance_x) * 0.5;
+                glyph.x0 += offset;
+                glyph.x1 += offset;
+                glyph.advance_x = min_advance_x;
+                changed = true;
+            }
+        }
+        if changed {
+            sys::ImFont_BuildLookupTable(font);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dpi_scale_after_prepare_texture() {
+        // The order of a HiDPI application: the renderer prepares the texture with the default
+        // font before the scale is known
+        let mut imgui = ImGui::init();
+        imgui.prepare_texture(|_| ());
+        imgui.set_dpi_scale(2.0);
+        assert!(imgui.fonts_dirty());
+        assert_eq!(imgui.fonts_mut().len(), 1);
+        imgui.prepare_texture(|_| ());
+        let size = unsafe { sys::ImFont_GetFontSize(FontId(0).font_ptr()) };
+        assert_eq!(size, 26.0);
+    }
+}
@@ -0,0 +1,239 @@
+#![warn(missing_docs)]
+use sys;
+use std::cmp;
+
+use sys::ImWchar;
+
+use super::Icon;
+
+type PresetFn = unsafe extern "C" fn(*mut sys::ImFontAtlas) -> *const ImWchar;
+
+/// A set of character ranges to bake into the font atlas.
+///
+/// Only characters of the Basic Multilingual Plane (U+0001 to U+FFFF) are supported by ImGui,
+/// other characters are ignored. Ranges are kept sorted and merged, so building the same set in
+/// a different order gives the same result.
+///
+/// # Example
+/// ```rust,no_run
+/// # use imgui::*;
+/// let ranges = GlyphRanges::default()
+///     .union(&GlyphRanges::cyrillic())
+///     .add_range('\u{2190}', '\u{2193}');
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlyphRanges {
+    ranges: Vec<(ImWchar, ImWchar)>,
+}
+
+impl Default for GlyphRanges {
+    /// Returns the ranges used by default: Basic Latin and Latin-1 Supplement.
+    fn default() -> GlyphRanges { GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesDefault) }
+}
+
+impl GlyphRanges {
+    /// Returns an empty set of ranges.
+    pub fn empty() -> GlyphRanges { GlyphRanges { ranges: Vec::new() } }
+    /// Returns the default ranges plus Korean characters.
+    pub fn korean() -> GlyphRanges { GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesKorean) }
+    /// Returns the default ranges plus Hiragana, Katakana, half-width characters and a selection
+    /// of 1946 common ideograms.
+    pub fn japanese() -> GlyphRanges {
+        GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesJapanese)
+    }
+    /// Returns the default ranges plus half-width characters and the full set of about 21000
+    /// CJK Unified Ideographs.
+    pub fn chinese() -> GlyphRanges { GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesChinese) }
+    /// Returns the default ranges plus about 400 Cyrillic characters.
+    pub fn cyrillic() -> GlyphRanges {
+        GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesCyrillic)
+    }
+    /// Returns the default ranges plus Thai characters.
+    pub fn thai() -> GlyphRanges { GlyphRanges::preset(sys::ImFontAtlas_GetGlyphRangesThai) }
+    /// Returns the minimal ranges containing every character of the given texts.
+    ///
+    /// This keeps the atlas small when only known strings (e.g. translations) have to be
+    /// displayed. Consider adding `?`, which ImGui displays in place of missing characters.
+    pub fn from_texts<I, S>(texts: I) -> GlyphRanges
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ranges = GlyphRanges::empty();
+        for text in texts {
+            ranges.push_text(text.as_ref());
+        }
+        ranges.normalize();
+        ranges
+    }
+    /// Adds an inclusive range of characters.
+    pub fn add_range(mut self, first: char, last: char) -> GlyphRanges {
+        let first = cmp::max(first as u32, 1);
+        let last = cmp::min(last as u32, 0xFFFF);
+        if first <= last {
+            self.ranges.push((first as ImWchar, last as ImWchar));
+            self.normalize();
+        }
+        self
+    }
+    /// Adds a single character.
+    pub fn add_char(self, c: char) -> GlyphRanges { self.add_range(c, c) }
+    /// Adds the character of an icon.
+    pub fn add_icon(self, icon: Icon) -> GlyphRanges { self.add_char(icon.char()) }
+    /// Adds every character of the text.
+    pub fn add_text(mut self, text: &str) -> GlyphRanges {
+        self.push_text(text);
+        self.normalize();
+        self
+    }
+    /// Adds all characters of another set of ranges.
+    pub fn union(mut self, other: &GlyphRanges) -> GlyphRanges {
+        self.ranges.extend_from_slice(&other.ranges);
+        self.normalize();
+        self
+    }
+    /// Returns true if the character is in one of the ranges.
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        self.ranges
+            .iter()
+            .any(|&(first, last)| first as u32 <= c && c <= last as u32)
+    }
+    /// Returns the sorted, non-overlapping inclusive ranges.
+    pub fn ranges(&self) -> &[(ImWchar, ImWchar)] { &self.ranges }
+    /// Returns the ranges in the zero-terminated format used by ImGui.
+    pub(crate) fn to_raw(&self) -> Vec<ImWchar> {
+        let mut raw = Vec::with_capacity(self.ranges.len() * 2 + 1);
+        for &(first, last) in &self.ranges {
+            raw.push(first);
+            raw.push(last);
+        }
+        raw.push(0);
+        raw
+    }
+    fn preset(f: PresetFn) -> GlyphRanges {
+        let mut ranges = GlyphRanges::empty();
+        unsafe {
+            // The presets are static arrays, the atlas is only needed as the receiver
+            let mut ptr = f((*sys::igGetIO()).fonts);
+            while *ptr != 0 {
+                ranges.ranges.push((*ptr, *ptr.offset(1)));
+                ptr = ptr.offset(2);
+            }
+        }
+        ranges.normalize();
+        ranges
+    }
+    fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            let c = c as u32;
+            if c >= 1 && c <= 0xFFFF {
+                self.ranges.push((c as ImWchar, c as ImWchar));
+            }
+        }
+    }
+    fn normalize(&mut self) {
+        self.ranges.sort();
+        let mut merged: Vec<(ImWchar, ImWchar)> = Vec::with_capacity(self.ranges.len());
+        for &(first, last) in &self.ranges {
+            if let Some(previous) = merged.last_mut() {
+                if first as u32 <= previous.1 as u32 + 1 {
+                    previous.1 = cmp::max(previous.1, last);
+                    continue;
+                }
+            }
+            merged.push((first, last));
+        }
+        self.ranges = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(first: char, last: char) -> (ImWchar, ImWchar) {
+        (first as ImWchar, last as ImWchar)
+    }
+
+    #[test]
+    fn test_merge_overlapping() {
+        let ranges = GlyphRanges::empty()
+            .add_range('d', 'k')
+            .add_range('a', 'f')
+            .add_range('e', 'g');
+        assert_eq!(ranges.ranges(), &[range('a', 'k')]);
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        let ranges = GlyphRanges::empty()
+            .add_range('a', 'c')
+            .add_range('d', 'f')
+            .add_range('h', 'j');
+        assert_eq!(ranges.ranges(), &[range('a', 'f'), range('h', 'j')]);
+        let ranges = GlyphRanges::empty()
+            .add_range('\u{fffe}', '\u{ffff}')
+            .add_char('\u{fffd}');
+        assert_eq!(ranges.ranges(), &[range('\u{fffd}', '\u{ffff}')]);
+    }
+
+    #[test]
+    fn test_order_independent() {
+        let a = GlyphRanges::empty().add_range('x', 'z').add_char('b').add_range('a', 'c');
+        let b = GlyphRanges::empty().add_range('a', 'c').add_range('x', 'z').add_char('b');
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_outside_bmp() {
+        let ranges = GlyphRanges::empty()
+            .add_char('\u{1f600}')
+            .add_range('\u{fff0}', '\u{10010}')
+            .add_range('\0', 'a');
+        assert_eq!(
+            ranges.ranges(),
+            &[range('\u{1}', 'a'), range('\u{fff0}', '\u{ffff}')]
+        );
+        assert!(!ranges.contains('\u{1f600}'));
+        assert!(!ranges.contains('\0'));
+    }
+
+    #[test]
+    fn test_from_texts() {
+        let ranges = GlyphRanges::from_texts(&["abba", "cab", ""]);
+        assert_eq!(ranges.ranges(), &[range('a', 'c')]);
+        let ranges = GlyphRanges::from_texts(vec!["hello".to_owned(), "\u{1f600}".to_owned()]);
+        assert_eq!(
+            ranges.ranges(),
+            &[range('e', 'e'), range('h', 'h'), range('l', 'l'), range('o', 'o')]
+        );
+        assert_eq!(
+            GlyphRanges::empty().add_text("hello"),
+            GlyphRanges::from_texts(&["hello"])
+        );
+    }
+
+    #[test]
+    fn test_union_and_contains() {
+        let ranges = GlyphRanges::empty()
+            .add_range('a', 'c')
+            .union(&GlyphRanges::empty().add_range('b', 'e').add_char('x'));
+        assert_eq!(ranges.ranges(), &[range('a', 'e'), range('x', 'x')]);
+        assert!(ranges.contains('a'));
+        assert!(ranges.contains('e'));
+        assert!(ranges.contains('x'));
+        assert!(!ranges.contains('f'));
+        assert!(!ranges.contains('w'));
+    }
+
+    #[test]
+    fn test_to_raw() {
+        let ranges = GlyphRanges::empty().add_range('x', 'z').add_range('a', 'c');
+        assert_eq!(
+            ranges.to_raw(),
+            vec!['a' as ImWchar, 'c' as ImWchar, 'x' as ImWchar, 'z' as ImWchar, 0]
+        );
+        assert_eq!(GlyphRanges::empty().to_raw(), vec![0]);
+    }
+}
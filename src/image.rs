@@ -0,0 +1,226 @@
+#![warn(missing_docs)]
+use sys;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+
+use super::{ImVec2, ImVec4, Ui};
+
+/// Identifier of a texture known to the renderer.
+///
+/// The value is passed untouched to the renderer through the draw commands, so its meaning
+/// (e.g. an OpenGL texture name or an index into a texture table) is up to the renderer. The
+/// bundled renderers look the textures up in their `Textures` table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextureId(usize);
+
+impl TextureId {
+    /// Creates a texture identifier from a raw value.
+    pub fn new(id: usize) -> Self { TextureId(id) }
+    /// Returns the raw value of the texture identifier.
+    pub fn id(self) -> usize { self.0 }
+    pub(crate) fn as_ptr(self) -> sys::ImTextureID { self.0 as *mut c_void }
+}
+
+impl From<usize> for TextureId {
+    fn from(id: usize) -> TextureId { TextureId(id) }
+}
+
+impl From<*mut c_void> for TextureId {
+    fn from(id: *mut c_void) -> TextureId { TextureId(id as usize) }
+}
+
+/// Table of the textures of a renderer, which hands out the ids to draw them with.
+///
+/// # Example
+/// ```rust,ignore
+/// let texture_id = renderer.textures().insert(my_texture);
+/// // Every frame
+/// ui.image(texture_id, (100.0, 100.0)).build();
+/// ```
+#[derive(Debug)]
+pub struct Textures<T> {
+    textures: HashMap<usize, T>,
+    next: usize,
+}
+
+impl<T> Textures<T> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Textures {
+            textures: HashMap::new(),
+            next: 0,
+        }
+    }
+    /// Adds a texture and returns its id. Ids aren't reused after a texture has been removed.
+    pub fn insert(&mut self, texture: T) -> TextureId {
+        let id = self.next;
+        self.textures.insert(id, texture);
+        self.next += 1;
+        TextureId(id)
+    }
+    /// Replaces the texture with the given id, and returns the previous texture.
+    pub fn replace(&mut self, id: TextureId, texture: T) -> Option<T> {
+        self.textures.insert(id.0, texture)
+    }
+    /// Removes the texture with the given id, and returns it.
+    pub fn remove(&mut self, id: TextureId) -> Option<T> { self.textures.remove(&id.0) }
+    /// Returns the texture with the given id.
+    pub fn get(&self, id: TextureId) -> Option<&T> { self.textures.get(&id.0) }
+}
+
+impl<T> Default for Textures<T> {
+    fn default() -> Self { Textures::new() }
+}
+
+/// Builder for an image widget.
+#[must_use]
+pub struct Image<'ui> {
+    texture_id: TextureId,
+    size: ImVec2,
+    uv0: ImVec2,
+    uv1: ImVec2,
+    tint_col: ImVec4,
+    border_col: ImVec4,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui> Image<'ui> {
+    /// Constructs a new image builder.
+    pub fn new<S: Into<ImVec2>>(_: &Ui<'ui>, texture_id: TextureId, size: S) -> Self {
+        Image {
+            texture_id: texture_id,
+            size: size.into(),
+            uv0: ImVec2::new(0.0, 0.0),
+            uv1: ImVec2::new(1.0, 1.0),
+            tint_col: ImVec4::new(1.0, 1.0, 1.0, 1.0),
+            border_col: ImVec4::new(0.0, 0.0, 0.0, 0.0),
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the image size.
+    #[inline]
+    pub fn size<S: Into<ImVec2>>(mut self, size: S) -> Self {
+        self.size = size.into();
+        self
+    }
+    /// Sets the texture coordinates of the upper left corner (default: (0.0, 0.0)).
+    #[inline]
+    pub fn uv0<T: Into<ImVec2>>(mut self, uv0: T) -> Self {
+        self.uv0 = uv0.into();
+        self
+    }
+    /// Sets the texture coordinates of the lower right corner (default: (1.0, 1.0)).
+    #[inline]
+    pub fn uv1<T: Into<ImVec2>>(mut self, uv1: T) -> Self {
+        self.uv1 = uv1.into();
+        self
+    }
+    /// Sets the color multiplied with the texture (default: white, no tint).
+    #[inline]
+    pub fn tint_col<C: Into<ImVec4>>(mut self, tint_col: C) -> Self {
+        self.tint_col = tint_col.into();
+        self
+    }
+    /// Sets the border color (default: transparent, no border).
+    #[inline]
+    pub fn border_col<C: Into<ImVec4>>(mut self, border_col: C) -> Self {
+        self.border_col = border_col.into();
+        self
+    }
+    /// Builds the image.
+    pub fn build(self) {
+        unsafe {
+            sys::igImage(
+                self.texture_id.as_ptr(),
+                self.size,
+                self.uv0,
+                self.uv1,
+                self.tint_col,
+                self.border_col,
+            );
+        }
+    }
+}
+
+/// Builder for an image button widget.
+#[must_use]
+pub struct ImageButton<'ui> {
+    texture_id: TextureId,
+    size: ImVec2,
+    uv0: ImVec2,
+    uv1: ImVec2,
+    frame_padding: i32,
+    bg_col: ImVec4,
+    tint_col: ImVec4,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui> ImageButton<'ui> {
+    /// Constructs a new image button builder.
+    pub fn new<S: Into<ImVec2>>(_: &Ui<'ui>, texture_id: TextureId, size: S) -> Self {
+        ImageButton {
+            texture_id: texture_id,
+            size: size.into(),
+            uv0: ImVec2::new(0.0, 0.0),
+            uv1: ImVec2::new(1.0, 1.0),
+            frame_padding: -1,
+            bg_col: ImVec4::new(0.0, 0.0, 0.0, 0.0),
+            tint_col: ImVec4::new(1.0, 1.0, 1.0, 1.0),
+            _phantom: PhantomData,
+        }
+    }
+    /// Sets the image size.
+    #[inline]
+    pub fn size<S: Into<ImVec2>>(mut self, size: S) -> Self {
+        self.size = size.into();
+        self
+    }
+    /// Sets the texture coordinates of the upper left corner (default: (0.0, 0.0)).
+    #[inline]
+    pub fn uv0<T: Into<ImVec2>>(mut self, uv0: T) -> Self {
+        self.uv0 = uv0.into();
+        self
+    }
+    /// Sets the texture coordinates of the lower right corner (default: (1.0, 1.0)).
+    #[inline]
+    pub fn uv1<T: Into<ImVec2>>(mut self, uv1: T) -> Self {
+        self.uv1 = uv1.into();
+        self
+    }
+    /// Sets the frame padding in pixels.
+    ///
+    /// Use a negative value to use the default frame padding from the style, or 0 for no
+    /// padding.
+    #[inline]
+    pub fn frame_padding(mut self, frame_padding: i32) -> Self {
+        self.frame_padding = frame_padding;
+        self
+    }
+    /// Sets the background color (default: transparent).
+    #[inline]
+    pub fn bg_col<C: Into<ImVec4>>(mut self, bg_col: C) -> Self {
+        self.bg_col = bg_col.into();
+        self
+    }
+    /// Sets the color multiplied with the texture (default: white, no tint).
+    #[inline]
+    pub fn tint_col<C: Into<ImVec4>>(mut self, tint_col: C) -> Self {
+        self.tint_col = tint_col.into();
+        self
+    }
+    /// Builds the image button. Returns true if the button was pressed.
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igImageButton(
+                self.texture_id.as_ptr(),
+                self.size,
+                self.uv0,
+                self.uv1,
+                self.frame_padding,
+                self.bg_col,
+                self.tint_col,
+            )
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub extern crate imgui_sys as sys;
 
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::ffi::CStr;
 use std::mem;
 use std::os::raw::{c_char, c_float, c_int, c_uchar, c_void};
@@ -7,13 +9,28 @@ use std::ptr;
 use std::slice;
 use std::str;
 use sys::ImGuiStyleVar;
+use draw_list::OwnedDrawList;
+use fonts::GlyphMinAdvance;
+use style::scale_style_sizes;
 
-pub use sys::{ImDrawIdx, ImDrawVert, ImGuiColorEditFlags, ImGuiHoveredFlags, ImGuiInputTextFlags,
-              ImGuiKey, ImGuiMouseCursor, ImGuiSelectableFlags, ImGuiCond, ImGuiCol, ImGuiStyle,
-              ImGuiTreeNodeFlags, ImGuiWindowFlags, ImVec2, ImVec4};
+pub use sys::{ImDrawCmd, ImDrawCornerFlags, ImDrawIdx, ImDrawVert, ImGuiColorEditFlags,
+              ImGuiHoveredFlags, ImGuiInputTextFlags, ImGuiKey, ImGuiMouseCursor,
+              ImGuiSelectableFlags, ImGuiCond, ImGuiCol, ImGuiStyle, ImGuiTreeNodeFlags,
+              ImGuiWindowFlags, ImVec2, ImVec4, ImWchar};
 pub use child_frame::ChildFrame;
 pub use color_editors::{ColorButton, ColorEdit, ColorEditMode, ColorFormat, ColorPicker,
                         ColorPickerMode, ColorPreview, EditableColor};
+pub use combo::Combo;
+pub use display_format::{DisplayFormat, DisplayFormatError};
+pub use drag::{DragFloat, DragFloat2, DragFloat3, DragFloat4, DragFloatRange2, DragInt,
+               DragInt2, DragInt3, DragInt4, DragIntRange2};
+pub use draw_data::{DrawData, DrawListIter};
+pub use draw_list::{BezierCurve, ChannelsSplit, Circle, DrawCallback, DrawCallbackContext,
+                    DrawImage, DrawText, ImColor, Line, MeshWriter, Path, Polyline, Quad, Rect,
+                    Triangle, WindowDrawList};
+pub use fonts::{Font, FontAtlas, FontConfig, FontGlyph, FontId, Icon};
+pub use glyph_ranges::GlyphRanges;
+pub use image::{Image, ImageButton, TextureId, Textures};
 pub use input::{InputFloat, InputFloat2, InputFloat3, InputFloat4, InputInt, InputInt2, InputInt3,
                 InputInt4, InputText};
 pub use menus::{Menu, MenuItem};
@@ -21,7 +38,7 @@ pub use plothistogram::PlotHistogram;
 pub use plotlines::PlotLines;
 pub use progressbar::ProgressBar;
 pub use sliders::{SliderFloat, SliderFloat2, SliderFloat3, SliderFloat4, SliderInt, SliderInt2,
-                  SliderInt3, SliderInt4};
+                  SliderInt3, SliderInt4, VerticalSliderFloat, VerticalSliderInt};
 pub use string::{ImStr, ImString};
 pub use style::StyleVar;
 pub use trees::{CollapsingHeader, TreeNode};
@@ -29,11 +46,20 @@ pub use window::Window;
 
 mod child_frame;
 mod color_editors;
+mod combo;
+mod display_format;
+mod drag;
+mod draw_data;
+mod draw_list;
+mod fonts;
+mod glyph_ranges;
+mod image;
 mod input;
 mod menus;
 mod plothistogram;
 mod plotlines;
 mod progressbar;
+pub mod render_util;
 mod sliders;
 mod string;
 mod style;
@@ -45,6 +71,18 @@ pub struct ImGui {
     // lives long enough in case the ImStr contains a Cow::Owned
     ini_filename: Option<ImString>,
     log_filename: Option<ImString>,
+    // Same for the glyph ranges of the fonts in the atlas
+    font_glyph_ranges: Vec<Vec<ImWchar>>,
+    font_configs: Vec<FontConfig>,
+    glyph_min_advances: Vec<GlyphMinAdvance>,
+    fonts_dirty: bool,
+    dpi_scale: f32,
+    // Closures of the draw callbacks of the current frame
+    draw_callbacks: RefCell<Vec<Option<DrawCallback>>>,
+    // Draw lists with a live WindowDrawList, which would otherwise alias each other's paths
+    draw_lists_in_use: RefCell<Vec<*mut sys::ImDrawList>>,
+    background_draw_list: OwnedDrawList,
+    overlay_draw_list: OwnedDrawList,
 }
 
 #[macro_export]
@@ -96,16 +134,61 @@ impl ImGui {
         ImGui {
             ini_filename: None,
             log_filename: None,
+            font_glyph_ranges: Vec::new(),
+            font_configs: Vec::new(),
+            glyph_min_advances: Vec::new(),
+            fonts_dirty: true,
+            dpi_scale: 1.0,
+            draw_callbacks: RefCell::new(Vec::new()),
+            draw_lists_in_use: RefCell::new(Vec::new()),
+            background_draw_list: OwnedDrawList::new(),
+            overlay_draw_list: OwnedDrawList::new(),
         }
     }
     fn io(&self) -> &sys::ImGuiIO { unsafe { &*sys::igGetIO() } }
     fn io_mut(&mut self) -> &mut sys::ImGuiIO { unsafe { &mut *sys::igGetIO() } }
     pub fn style(&self) -> &ImGuiStyle { unsafe { &*sys::igGetStyle() } }
     pub fn style_mut(&mut self) -> &mut ImGuiStyle { unsafe { &mut *sys::igGetStyle() } }
+    /// Returns the font atlas, for adding fonts.
+    ///
+    /// The renderer prepares the font texture when it is initialized. Fonts changed afterwards
+    /// are only visible once the texture has been reloaded, e.g. with the renderer's
+    /// `reload_font_texture` method (see `fonts_dirty`).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// let mut imgui = ImGui::init();
+    /// let default = imgui.fonts_mut().add_font_default(&FontConfig::new());
+    /// let big = imgui.fonts_mut()
+    ///     .add_font_from_file("DroidSans.ttf", &FontConfig::new().size_pixels(20.0))
+    ///     .expect("Failed to load font");
+    /// ```
+    pub fn fonts_mut<'a>(&'a mut self) -> FontAtlas<'a> { FontAtlas::new(self) }
+    /// Returns true if the font atlas has changed since the font texture was last prepared.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// imgui.fonts_mut().clear();
+    /// imgui.fonts_mut().add_font_default(&FontConfig::new().size_pixels(26.0));
+    /// if imgui.fonts_dirty() {
+    ///     renderer.reload_font_texture(&mut imgui)?;
+    /// }
+    /// ```
+    pub fn fonts_dirty(&self) -> bool { self.fonts_dirty }
+    /// Builds the font atlas if needed and passes its RGBA32 pixels to the closure, which
+    /// usually uploads them to a texture.
+    ///
+    /// If no font has been added, the default font is added first.
     pub fn prepare_texture<'a, F, T>(&mut self, f: F) -> T
     where
         F: FnOnce(TextureHandle<'a>) -> T,
     {
+        if self.fonts_mut().is_empty() {
+            // ImGui would add it on its own, without a configuration for set_dpi_scale
+            self.fonts_mut().add_font_default(&FontConfig::new());
+        }
+        self.fonts_dirty = false;
         let io = self.io();
         let mut pixels: *mut c_uchar = ptr::null_mut();
         let mut width: c_int = 0;
@@ -119,6 +202,9 @@ impl ImGui {
                 &mut height,
                 &mut bytes_per_pixel,
             );
+            for glyph_min_advance in &self.glyph_min_advances {
+                glyph_min_advance.apply(io.fonts, self.dpi_scale);
+            }
             f(TextureHandle {
                 width: width as u32,
                 height: height as u32,
@@ -155,6 +241,36 @@ impl ImGui {
         let io = self.io_mut();
         io.ini_saving_rate = value;
     }
+    /// Returns the DPI scale factor (default: 1.0).
+    pub fn dpi_scale(&self) -> f32 { self.dpi_scale }
+    /// Sets the DPI scale factor, e.g. 2.0 on a HiDPI display whose size is given to `frame`
+    /// in physical pixels.
+    ///
+    /// Fonts are rebuilt at their configured pixel size multiplied by the scale, so the font
+    /// texture has to be reloaded before the next frame (see `fonts_dirty`). If no font has
+    /// been added yet, the default font is added so that it can be scaled.
+    ///
+    /// Every size of the style is multiplied by `scale / dpi_scale()`, so changes made with
+    /// `style_mut` are kept across scale changes.
+    ///
+    /// # Panics
+    /// Panics if the scale isn't positive, or if fonts were added to the atlas through
+    /// `imgui-sys` instead of `fonts_mut`.
+    pub fn set_dpi_scale(&mut self, scale: f32) {
+        assert!(scale > 0.0, "DPI scale must be positive");
+        if scale == self.dpi_scale {
+            return;
+        }
+        let factor = scale / self.dpi_scale;
+        scale_style_sizes(self.style_mut(), factor);
+        self.dpi_scale = scale;
+        let mut fonts = self.fonts_mut();
+        if fonts.is_empty() {
+            fonts.add_font_default(&FontConfig::new());
+        } else {
+            fonts.apply_dpi_scale();
+        }
+    }
     pub fn set_font_global_scale(&mut self, value: f32) {
         let io = self.io_mut();
         io.font_global_scale = value;
@@ -332,8 +448,11 @@ impl ImGui {
             };
             io.delta_time = delta_time;
         }
+        self.draw_callbacks.borrow_mut().clear();
         unsafe {
             sys::igNewFrame();
+            self.background_draw_list.new_frame();
+            self.overlay_draw_list.new_frame();
             CURRENT_UI = Some(Ui { imgui: mem::transmute(self as &'a ImGui) });
         }
         Ui { imgui: self }
@@ -357,6 +476,16 @@ pub struct DrawList<'a> {
     pub vtx_buffer: &'a [sys::ImDrawVert],
 }
 
+impl<'a> DrawList<'a> {
+    unsafe fn from_raw(list: *const sys::ImDrawList) -> DrawList<'a> {
+        DrawList {
+            cmd_buffer: (*list).cmd_buffer.as_slice(),
+            idx_buffer: (*list).idx_buffer.as_slice(),
+            vtx_buffer: (*list).vtx_buffer.as_slice(),
+        }
+    }
+}
+
 pub struct Ui<'ui> {
     imgui: &'ui ImGui,
 }
@@ -399,21 +528,48 @@ impl<'ui> Ui<'ui> {
     where
         F: FnMut(&Ui, DrawList) -> Result<(), E>,
     {
-        unsafe {
+        self.render_draw_data(|ui, draw_data| {
+            for draw_list in draw_data.draw_lists() {
+                try!(f(ui, draw_list));
+            }
+            Ok(())
+        })
+    }
+    /// Renders the frame and passes all of its draw lists to `f` at once, so that they can be
+    /// post-processed (e.g. with `DrawData::scale_clip_rects`) before they are rendered.
+    pub fn render_draw_data<F, E>(self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&Ui, DrawData) -> Result<(), E>,
+    {
+        let result = unsafe {
             sys::igRender();
 
+            let mut lists = Vec::new();
+            let background = &self.imgui.background_draw_list;
+            if background.prepare_render() {
+                lists.push(background.raw());
+            }
             let draw_data = sys::igGetDrawData();
             for &cmd_list in (*draw_data).cmd_lists() {
-                let draw_list = DrawList {
-                    cmd_buffer: (*cmd_list).cmd_buffer.as_slice(),
-                    idx_buffer: (*cmd_list).idx_buffer.as_slice(),
-                    vtx_buffer: (*cmd_list).vtx_buffer.as_slice(),
-                };
-                try!(f(&self, draw_list));
+                lists.push(cmd_list as *mut sys::ImDrawList);
+            }
+            let overlay = &self.imgui.overlay_draw_list;
+            if overlay.prepare_render() {
+                lists.push(overlay.raw());
             }
+            f(&self, DrawData::new(lists))
+        };
+        unsafe {
             CURRENT_UI = None;
         }
-        Ok(())
+        result
+    }
+    /// Takes the closure of a draw command added with `WindowDrawList::add_callback`, for
+    /// running it when the renderer reaches the command.
+    ///
+    /// Returns `None` for other commands, and if the closure has already been taken.
+    pub fn take_draw_callback(&self, cmd: &ImDrawCmd) -> Option<DrawCallback> {
+        draw_list::take_draw_callback(self.imgui, cmd)
     }
     pub fn show_user_guide(&self) { unsafe { sys::igShowUserGuide() }; }
     pub fn show_default_style_editor(&self) { unsafe { sys::igShowStyleEditor(ptr::null_mut()) }; }
@@ -517,6 +673,85 @@ impl<'ui> Ui<'ui> {
     }
 }
 
+// Fonts
+impl<'ui> Ui<'ui> {
+    /// Pushes a font to the font stack. Following widgets use this font until it is popped.
+    ///
+    /// # Panics
+    /// Panics if the font hasn't been built into the font texture yet.
+    pub fn push_font(&self, id: FontId) { unsafe { sys::igPushFont(id.font_ptr()) } }
+
+    /// Pops a font from the font stack.
+    ///
+    /// # Aborts
+    /// The current process is aborted if the font stack is empty.
+    pub fn pop_font(&self) { unsafe { sys::igPopFont() } }
+
+    /// Runs a function after temporarily pushing a font to the font stack.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// let mono = imgui.fonts_mut()
+    ///     .add_font_from_file("DroidSansMono.ttf", &FontConfig::new())
+    ///     .expect("Failed to load font");
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// ui.with_font(mono, || {
+    ///     ui.text(im_str!("let answer = 42;"));
+    /// });
+    /// ```
+    pub fn with_font<F>(&self, id: FontId, f: F)
+    where
+        F: FnOnce(),
+    {
+        self.push_font(id);
+        f();
+        self.pop_font();
+    }
+
+    /// Returns the font used by the following widgets.
+    pub fn current_font(&self) -> FontId { FontId::from_ptr(unsafe { sys::igGetFont() }) }
+
+    /// Returns a font of the atlas, for querying its metrics.
+    ///
+    /// # Panics
+    /// Panics if the font hasn't been built into the font texture yet.
+    pub fn font(&self, id: FontId) -> Font<'ui> { Font::new(self, id) }
+
+    /// Returns the size in pixels of the current font, including the window font scale and
+    /// the global font scale.
+    pub fn current_font_size(&self) -> f32 { unsafe { sys::igGetFontSize() } }
+
+    /// Sets the font scale of the current window.
+    pub fn set_window_font_scale(&self, scale: f32) { unsafe { sys::igSetWindowFontScale(scale) } }
+}
+
+// Draw list
+impl<'ui> Ui<'ui> {
+    /// Returns the draw list of the current window, for drawing custom shapes.
+    ///
+    /// # Panics
+    /// Panics if the draw list of the current window is still borrowed by another
+    /// `WindowDrawList`.
+    pub fn get_window_draw_list(&self) -> WindowDrawList<'ui> { WindowDrawList::new(self) }
+    /// Returns a draw list covering the whole display, which is rendered after all windows,
+    /// e.g. for debug overlays or drag previews.
+    ///
+    /// # Panics
+    /// Panics if the overlay draw list is still borrowed by another `WindowDrawList`.
+    pub fn get_overlay_draw_list(&self) -> WindowDrawList<'ui> {
+        WindowDrawList::from_owned(self, &self.imgui.overlay_draw_list)
+    }
+    /// Returns a draw list covering the whole display, which is rendered before all windows.
+    ///
+    /// # Panics
+    /// Panics if the background draw list is still borrowed by another `WindowDrawList`.
+    pub fn get_background_draw_list(&self) -> WindowDrawList<'ui> {
+        WindowDrawList::from_owned(self, &self.imgui.background_draw_list)
+    }
+}
+
 // Widgets
 impl<'ui> Ui<'ui> {
     pub fn text<T: AsRef<str>>(&self, text: T) {
@@ -571,6 +806,34 @@ impl<'ui> Ui<'ui> {
     }
 }
 
+// Widgets: Images
+impl<'ui> Ui<'ui> {
+    /// Constructs a new image builder.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use imgui::*;
+    /// # let mut imgui = ImGui::init();
+    /// # let ui = imgui.frame((0, 0), (0, 0), 0.1);
+    /// # let my_texture_id = Textures::new().insert(());
+    /// ui.image(my_texture_id, (100.0, 100.0))
+    ///     .uv1((0.5, 0.5))
+    ///     .border_col((1.0, 1.0, 1.0, 1.0))
+    ///     .build();
+    /// ```
+    pub fn image<S: Into<ImVec2>>(&self, texture_id: TextureId, size: S) -> Image<'ui> {
+        Image::new(self, texture_id, size)
+    }
+    /// Constructs a new image button builder.
+    pub fn image_button<S: Into<ImVec2>>(
+        &self,
+        texture_id: TextureId,
+        size: S,
+    ) -> ImageButton<'ui> {
+        ImageButton::new(self, texture_id, size)
+    }
+}
+
 // Widgets: Input
 impl<'ui> Ui<'ui> {
     pub fn input_text<'p>(&self, label: &'p ImStr, buf: &'p mut ImString) -> InputText<'ui, 'p> {
@@ -614,6 +877,74 @@ impl<'ui> Ui<'ui> {
     }
 }
 
+// Widgets: Drags
+impl<'ui> Ui<'ui> {
+    pub fn drag_float<'p>(&self, label: &'p ImStr, value: &'p mut f32) -> DragFloat<'ui, 'p> {
+        DragFloat::new(self, label, value)
+    }
+    pub fn drag_float2<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [f32; 2],
+    ) -> DragFloat2<'ui, 'p> {
+        DragFloat2::new(self, label, value)
+    }
+    pub fn drag_float3<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [f32; 3],
+    ) -> DragFloat3<'ui, 'p> {
+        DragFloat3::new(self, label, value)
+    }
+    pub fn drag_float4<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [f32; 4],
+    ) -> DragFloat4<'ui, 'p> {
+        DragFloat4::new(self, label, value)
+    }
+    pub fn drag_float_range2<'p>(
+        &self,
+        label: &'p ImStr,
+        current_min: &'p mut f32,
+        current_max: &'p mut f32,
+    ) -> DragFloatRange2<'ui, 'p> {
+        DragFloatRange2::new(self, label, current_min, current_max)
+    }
+    pub fn drag_int<'p>(&self, label: &'p ImStr, value: &'p mut i32) -> DragInt<'ui, 'p> {
+        DragInt::new(self, label, value)
+    }
+    pub fn drag_int2<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [i32; 2],
+    ) -> DragInt2<'ui, 'p> {
+        DragInt2::new(self, label, value)
+    }
+    pub fn drag_int3<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [i32; 3],
+    ) -> DragInt3<'ui, 'p> {
+        DragInt3::new(self, label, value)
+    }
+    pub fn drag_int4<'p>(
+        &self,
+        label: &'p ImStr,
+        value: &'p mut [i32; 4],
+    ) -> DragInt4<'ui, 'p> {
+        DragInt4::new(self, label, value)
+    }
+    pub fn drag_int_range2<'p>(
+        &self,
+        label: &'p ImStr,
+        current_min: &'p mut i32,
+        current_max: &'p mut i32,
+    ) -> DragIntRange2<'ui, 'p> {
+        DragIntRange2::new(self, label, current_min, current_max)
+    }
+}
+
 // Widgets: Sliders
 impl<'ui> Ui<'ui> {
     pub fn slider_float<'p>(
@@ -688,6 +1019,28 @@ impl<'ui> Ui<'ui> {
     ) -> SliderInt4<'ui, 'p> {
         SliderInt4::new(self, label, value, min, max)
     }
+    /// Returns a builder for a vertical slider of the given size.
+    pub fn vslider_float<'p, S: Into<ImVec2>>(
+        &self,
+        label: &'p ImStr,
+        size: S,
+        value: &'p mut f32,
+        min: f32,
+        max: f32,
+    ) -> VerticalSliderFloat<'ui, 'p> {
+        VerticalSliderFloat::new(self, label, size, value, min, max)
+    }
+    /// Returns a builder for a vertical slider of the given size.
+    pub fn vslider_int<'p, S: Into<ImVec2>>(
+        &self,
+        label: &'p ImStr,
+        size: S,
+        value: &'p mut i32,
+        min: i32,
+        max: i32,
+    ) -> VerticalSliderInt<'ui, 'p> {
+        VerticalSliderInt::new(self, label, size, value, min, max)
+    }
 }
 
 // Widgets: Color Editor/Picker
@@ -861,6 +1214,18 @@ impl<'ui> Ui<'ui> {
             )
         }
     }
+    /// Returns a builder for a combo box over `items`, which are labeled by `item_label`.
+    pub fn combo_items<'p, T, L>(
+        &self,
+        label: &'p ImStr,
+        items: &'p [T],
+        item_label: L,
+    ) -> Combo<'ui, 'p, T, L>
+    where
+        L: Fn(&T) -> Cow<ImStr>,
+    {
+        Combo::new(self, label, items, item_label)
+    }
 }
 
 // Widgets: ListBox
@@ -0,0 +1,61 @@
+//! Helpers shared by the renderers.
+#![warn(missing_docs)]
+use sys::ImU32;
+use std::cmp;
+
+/// Returns a table converting 8-bit sRGB color components to linear ones, for
+/// `linearize_color`.
+pub fn srgb_to_linear_table() -> [u8; 256] {
+    let mut table = [0; 256];
+    for (i, value) in table.iter_mut().enumerate() {
+        let c = i as f32 / 255.0;
+        let linear = if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        };
+        *value = (linear * 255.0).round() as u8;
+    }
+    table
+}
+
+/// Converts the RGB components of a packed `ImU32` color with a table returned by
+/// `srgb_to_linear_table`, leaving alpha untouched.
+pub fn linearize_color(table: &[u8; 256], col: ImU32) -> ImU32 {
+    let r = table[(col & 0xff) as usize] as u32;
+    let g = table[(col >> 8 & 0xff) as usize] as u32;
+    let b = table[(col >> 16 & 0xff) as usize] as u32;
+    (col & 0xff00_0000) | b << 16 | g << 8 | r
+}
+
+/// Returns the buffer capacity to allocate for `len` elements, doubling to amortize growth.
+pub fn grow_capacity(current: usize, len: usize) -> usize {
+    let mut capacity = cmp::max(current, 256);
+    while capacity < len {
+        capacity *= 2;
+    }
+    capacity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linearize_color() {
+        let table = srgb_to_linear_table();
+        assert_eq!(table[0], 0);
+        assert_eq!(table[255], 255);
+        assert_eq!(table[128], 55);
+        // Alpha stays as is, the color components are converted separately
+        assert_eq!(linearize_color(&table, 0x80ff_8000), 0x80ff_3700);
+    }
+
+    #[test]
+    fn test_grow_capacity() {
+        assert_eq!(grow_capacity(0, 0), 256);
+        assert_eq!(grow_capacity(0, 300), 512);
+        assert_eq!(grow_capacity(1024, 300), 1024);
+        assert_eq!(grow_capacity(512, 2049), 4096);
+    }
+}
@@ -1,7 +1,9 @@
 use sys;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
-use super::{ImStr, Ui};
+use super::display_format::{default_float_format, default_int_format};
+use super::{DisplayFormat, ImStr, ImVec2, Ui};
 
 // TODO: Consider using Range, even though it is half-open
 
@@ -11,7 +13,7 @@ pub struct SliderInt<'ui, 'p> {
     value: &'p mut i32,
     min: i32,
     max: i32,
-    display_format: &'p ImStr,
+    display_format: Cow<'p, ImStr>,
     _phantom: PhantomData<&'ui Ui<'ui>>,
 }
 
@@ -22,13 +24,23 @@ impl<'ui, 'p> SliderInt<'ui, 'p> {
             value: value,
             min: min,
             max: max,
-            display_format: unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.0f\0") },
+            display_format: default_int_format(),
             _phantom: PhantomData,
         }
     }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
     #[inline]
     pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
-        self.display_format = display_format;
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes. While the value is dragged, ImGui truncates it instead of rounding it.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(i32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
         self
     }
     pub fn build(self) -> bool {
@@ -52,7 +64,7 @@ macro_rules! impl_slider_intn {
             value: &'p mut [i32; $N],
             min: i32,
             max: i32,
-            display_format: &'p ImStr,
+            display_format: Cow<'p, ImStr>,
             _phantom: PhantomData<&'ui Ui<'ui>>,
         }
 
@@ -63,13 +75,15 @@ macro_rules! impl_slider_intn {
                     value: value,
                     min: min,
                     max: max,
-                    display_format: unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.0f\0") },
+                    display_format: default_int_format(),
                     _phantom: PhantomData,
                 }
             }
+            /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+            /// shown literally.
             #[inline]
             pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
-                self.display_format = display_format;
+                self.display_format = DisplayFormat::sanitize(display_format);
                 self
             }
             pub fn build(self) -> bool {
@@ -96,7 +110,7 @@ pub struct SliderFloat<'ui, 'p> {
     value: &'p mut f32,
     min: f32,
     max: f32,
-    display_format: &'p ImStr,
+    display_format: Cow<'p, ImStr>,
     power: f32,
     _phantom: PhantomData<&'ui Ui<'ui>>,
 }
@@ -108,14 +122,24 @@ impl<'ui, 'p> SliderFloat<'ui, 'p> {
             value: value,
             min: min,
             max: max,
-            display_format: unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.3f\0") },
+            display_format: default_float_format(),
             power: 1.0,
             _phantom: PhantomData,
         }
     }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
     #[inline]
     pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
-        self.display_format = display_format;
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(f32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
         self
     }
     #[inline]
@@ -145,7 +169,7 @@ macro_rules! impl_slider_floatn {
             value: &'p mut [f32; $N],
             min: f32,
             max: f32,
-            display_format: &'p ImStr,
+            display_format: Cow<'p, ImStr>,
             power: f32,
             _phantom: PhantomData<&'ui Ui<'ui>>,
         }
@@ -157,14 +181,16 @@ macro_rules! impl_slider_floatn {
                     value: value,
                     min: min,
                     max: max,
-                    display_format: unsafe { ImStr::from_utf8_with_nul_unchecked(b"%.3f\0") },
+                    display_format: default_float_format(),
                     power: 1.0,
                     _phantom: PhantomData,
                 }
             }
+            /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+            /// shown literally.
             #[inline]
             pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
-                self.display_format = display_format;
+                self.display_format = DisplayFormat::sanitize(display_format);
                 self
             }
             #[inline]
@@ -190,3 +216,139 @@ macro_rules! impl_slider_floatn {
 impl_slider_floatn!(SliderFloat2, 2, igSliderFloat2);
 impl_slider_floatn!(SliderFloat3, 3, igSliderFloat3);
 impl_slider_floatn!(SliderFloat4, 4, igSliderFloat4);
+
+#[must_use]
+pub struct VerticalSliderInt<'ui, 'p> {
+    label: &'p ImStr,
+    size: ImVec2,
+    value: &'p mut i32,
+    min: i32,
+    max: i32,
+    display_format: Cow<'p, ImStr>,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> VerticalSliderInt<'ui, 'p> {
+    pub fn new<S: Into<ImVec2>>(
+        _: &Ui<'ui>,
+        label: &'p ImStr,
+        size: S,
+        value: &'p mut i32,
+        min: i32,
+        max: i32,
+    ) -> Self {
+        VerticalSliderInt {
+            label: label,
+            size: size.into(),
+            value: value,
+            min: min,
+            max: max,
+            display_format: default_int_format(),
+            _phantom: PhantomData,
+        }
+    }
+    #[inline]
+    pub fn size<S: Into<ImVec2>>(mut self, size: S) -> Self {
+        self.size = size.into();
+        self
+    }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes. While the value is dragged, ImGui truncates it instead of rounding it.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(i32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igVSliderInt(
+                self.label.as_ptr(),
+                self.size,
+                self.value,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+            )
+        }
+    }
+}
+
+#[must_use]
+pub struct VerticalSliderFloat<'ui, 'p> {
+    label: &'p ImStr,
+    size: ImVec2,
+    value: &'p mut f32,
+    min: f32,
+    max: f32,
+    display_format: Cow<'p, ImStr>,
+    power: f32,
+    _phantom: PhantomData<&'ui Ui<'ui>>,
+}
+
+impl<'ui, 'p> VerticalSliderFloat<'ui, 'p> {
+    pub fn new<S: Into<ImVec2>>(
+        _: &Ui<'ui>,
+        label: &'p ImStr,
+        size: S,
+        value: &'p mut f32,
+        min: f32,
+        max: f32,
+    ) -> Self {
+        VerticalSliderFloat {
+            label: label,
+            size: size.into(),
+            value: value,
+            min: min,
+            max: max,
+            display_format: default_float_format(),
+            power: 1.0,
+            _phantom: PhantomData,
+        }
+    }
+    #[inline]
+    pub fn size<S: Into<ImVec2>>(mut self, size: S) -> Self {
+        self.size = size.into();
+        self
+    }
+    /// Sets the printf format of the value. Formats which `DisplayFormat::new` rejects are
+    /// shown literally.
+    #[inline]
+    pub fn display_format(mut self, display_format: &'p ImStr) -> Self {
+        self.display_format = DisplayFormat::sanitize(display_format);
+        self
+    }
+    /// Shows the value formatted by `f` instead of using a printf format. The text is
+    /// formatted when the widget is built, so it shows the value from before this frame's
+    /// changes.
+    #[inline]
+    pub fn display_format_with<F: FnOnce(f32) -> String>(mut self, f: F) -> Self {
+        self.display_format = DisplayFormat::literal(&f(*self.value)).into_cow();
+        self
+    }
+    #[inline]
+    pub fn power(mut self, power: f32) -> Self {
+        self.power = power;
+        self
+    }
+    pub fn build(self) -> bool {
+        unsafe {
+            sys::igVSliderFloat(
+                self.label.as_ptr(),
+                self.size,
+                self.value,
+                self.min,
+                self.max,
+                self.display_format.as_ptr(),
+                self.power,
+            )
+        }
+    }
+}
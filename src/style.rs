@@ -1,4 +1,4 @@
-use ImVec2;
+use {ImGuiStyle, ImVec2};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum StyleVar {
@@ -15,3 +15,29 @@ pub enum StyleVar {
     GrabMinSize(f32),
     ButtonTextAlign(ImVec2),
 }
+
+/// Multiplies every size of the style (padding, spacing, rounding, scrollbar size...) by the
+/// factor. Alignments, alpha and colors are left untouched.
+pub(crate) fn scale_style_sizes(style: &mut ImGuiStyle, factor: f32) {
+    fn scale(v: &mut ImVec2, factor: f32) {
+        v.x *= factor;
+        v.y *= factor;
+    }
+    scale(&mut style.window_padding, factor);
+    scale(&mut style.window_min_size, factor);
+    style.window_rounding *= factor;
+    style.child_window_rounding *= factor;
+    scale(&mut style.frame_padding, factor);
+    style.frame_rounding *= factor;
+    scale(&mut style.item_spacing, factor);
+    scale(&mut style.item_inner_spacing, factor);
+    scale(&mut style.touch_extra_padding, factor);
+    style.indent_spacing *= factor;
+    style.columns_min_spacing *= factor;
+    style.scrollbar_size *= factor;
+    style.scrollbar_rounding *= factor;
+    style.grab_min_size *= factor;
+    style.grab_rounding *= factor;
+    scale(&mut style.display_window_padding, factor);
+    scale(&mut style.display_safe_area_padding, factor);
+}